@@ -0,0 +1,155 @@
+// 相同命中值聚合，避免同一个值在日志中重复出现时产生海量结果行
+use std::collections::HashMap;
+
+use crate::MatchResult;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file_name: String,
+    pub line_number: String,
+}
+
+impl Location {
+    pub fn of(result: &MatchResult) -> Location {
+        Location {
+            file_name: result.file_name.clone(),
+            line_number: result.line_number.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AggregatedResult {
+    pub rule: String,
+    pub value: String,         // 归一化后的值，聚合的键之一
+    pub matched_text: String,  // 首次出现时的原始匹配值
+    pub origin_text: String,   // 首次出现时的上下文
+    pub first: Location,
+    pub last: Location,
+    pub total: usize,
+    pub file_counts: Vec<(String, usize)>,  // 每个文件中的出现次数，按首次出现顺序
+    pub samples: Vec<Location>,             // 出现位置示例
+}
+
+// 归一化匹配值：去掉空白和引号，统一小写
+pub fn normalize_value(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '"' && *c != '\'')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+// 按 (规则, 归一化值) 聚合，sample_limit 为每组保留的位置示例数量
+pub fn aggregate(results: &[MatchResult], sample_limit: usize) -> Vec<AggregatedResult> {
+    // 扫描是并发的，结果顺序不固定，先按文件和行号排序以确定首次/末次出现；匹配中止的记录不参与聚合
+    let mut ordered: Vec<&MatchResult> = results.iter().filter(|result| !result.is_issue()).collect();
+    ordered.sort_by(|a, b| {
+        a.file_name
+            .cmp(&b.file_name)
+            .then_with(|| line_key(&a.line_number).cmp(&line_key(&b.line_number)))
+    });
+
+    let mut groups: Vec<AggregatedResult> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for result in ordered {
+        // 经过解码的匹配值按解码后的值聚合
+        let value = normalize_value(result.decoded.as_ref().map_or(&result.matched_text, |d| &d.text));
+        let key = (result.rule.clone(), value.clone());
+        let location = Location::of(result);
+        match index.get(&key) {
+            Some(&i) => {
+                let group = &mut groups[i];
+                group.total += 1;
+                group.last = location.clone();
+                match group.file_counts.iter_mut().find(|(f, _)| *f == result.file_name) {
+                    Some((_, count)) => *count += 1,
+                    None => group.file_counts.push((result.file_name.clone(), 1)),
+                }
+                if group.samples.len() < sample_limit {
+                    group.samples.push(location);
+                }
+            }
+            None => {
+                index.insert(key, groups.len());
+                groups.push(AggregatedResult {
+                    rule: result.rule.clone(),
+                    value,
+                    matched_text: result.matched_text.clone(),
+                    origin_text: result.origin_text.clone(),
+                    first: location.clone(),
+                    last: location.clone(),
+                    total: 1,
+                    file_counts: vec![(result.file_name.clone(), 1)],
+                    samples: if sample_limit > 0 { vec![location] } else { Vec::new() },
+                });
+            }
+        }
+    }
+    groups
+}
+
+fn line_key(line_number: &str) -> usize {
+    line_number.parse().unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalize::Decoded;
+
+    fn result(rule: &str, matched_text: &str, file_name: &str, line_number: &str) -> MatchResult {
+        MatchResult {
+            rule: rule.to_string(),
+            matched_text: matched_text.to_string(),
+            file_name: file_name.to_string(),
+            line_number: line_number.to_string(),
+            origin_text: format!("{} 第 {} 行", file_name, line_number),
+            decoded: None,
+            issue: None,
+        }
+    }
+
+    #[test]
+    fn normalize_value_ignores_quotes_space_and_case() {
+        assert_eq!(normalize_value("Password = \"Secret\""), "password=secret");
+        assert_eq!(normalize_value("key='ABC'"), "key=abc");
+    }
+
+    #[test]
+    fn groups_by_rule_and_normalized_value() {
+        let results = vec![
+            result("key", "key=\"ABC\"", "b.log", "3"),
+            result("key", "key=abc", "a.log", "10"),
+            result("key", "key=abc", "a.log", "2"),
+            result("other", "key=abc", "a.log", "2"),
+            result("key", "key=def", "a.log", "5"),
+        ];
+        let groups = aggregate(&results, 2);
+        assert_eq!(groups.len(), 3);
+        let group = &groups[0];
+        assert_eq!((group.rule.as_str(), group.value.as_str(), group.total), ("key", "key=abc", 3));
+        // 按文件和行号（数值）排序后确定首次及末次出现
+        assert_eq!(group.first, Location { file_name: "a.log".to_string(), line_number: "2".to_string() });
+        assert_eq!(group.last, Location { file_name: "b.log".to_string(), line_number: "3".to_string() });
+        assert_eq!(group.origin_text, "a.log 第 2 行");
+        assert_eq!(group.file_counts, vec![("a.log".to_string(), 2), ("b.log".to_string(), 1)]);
+        assert_eq!(group.samples.len(), 2);
+        assert_eq!((groups[1].rule.as_str(), groups[1].total), ("other", 1));
+        assert_eq!((groups[2].value.as_str(), groups[2].total), ("key=def", 1));
+    }
+
+    #[test]
+    fn decoded_values_group_by_decoded_text_and_issues_are_skipped() {
+        let mut encoded = result("phone", "MTM4MDAxMzgwMDA=", "a.log", "1");
+        encoded.decoded = Some(Decoded { chain: vec!["base64".to_string()], text: "13800138000".to_string() });
+        let mut issue = result("phone", "", "a.log", "2");
+        issue.issue = Some("超出匹配次数限制".to_string());
+        let results = vec![encoded, issue, result("phone", "13800138000", "a.log", "3")];
+        let groups = aggregate(&results, 0);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].total, 2);
+        assert_eq!(groups[0].matched_text, "MTM4MDAxMzgwMDA=");
+        assert!(groups[0].samples.is_empty());
+    }
+}
@@ -0,0 +1,615 @@
+// APK/AAR 中的二进制文件：classes*.dex 的字符串表、编译后的 XML（如 AndroidManifest.xml）及 resources.arsc，
+// 解析成文本后按普通文件匹配
+use std::collections::HashSet;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AndroidFile {
+    Dex,
+    BinaryXml,      // 编译后的 XML，APK 中的 AndroidManifest.xml 及 res 下的 xml
+    ResourceTable,  // resources.arsc
+}
+
+// 资源文件的块类型，见 Android 源码 ResourceTypes.h
+const RES_STRING_POOL_TYPE: u16 = 0x0001;
+const RES_TABLE_TYPE: u16 = 0x0002;
+const RES_XML_TYPE: u16 = 0x0003;
+const RES_XML_START_NAMESPACE_TYPE: u16 = 0x0100;
+const RES_XML_END_NAMESPACE_TYPE: u16 = 0x0101;
+const RES_XML_START_ELEMENT_TYPE: u16 = 0x0102;
+const RES_XML_END_ELEMENT_TYPE: u16 = 0x0103;
+const RES_XML_CDATA_TYPE: u16 = 0x0104;
+const RES_TABLE_PACKAGE_TYPE: u16 = 0x0200;
+const RES_TABLE_TYPE_TYPE: u16 = 0x0201;
+
+const UTF8_FLAG: u32 = 1 << 8;
+const NO_INDEX: u32 = 0xffff_ffff;
+
+// Res_value 的数据类型
+const TYPE_NULL: u8 = 0x00;
+const TYPE_REFERENCE: u8 = 0x01;
+const TYPE_ATTRIBUTE: u8 = 0x02;
+const TYPE_STRING: u8 = 0x03;
+const TYPE_FLOAT: u8 = 0x04;
+const TYPE_INT_DEC: u8 = 0x10;
+const TYPE_INT_HEX: u8 = 0x11;
+const TYPE_INT_BOOLEAN: u8 = 0x12;
+
+// ResTable_type 及 ResTable_entry 的标志位
+const FLAG_SPARSE: u8 = 0x01;
+const FLAG_OFFSET16: u8 = 0x02;
+const FLAG_COMPLEX: u16 = 0x0001;
+const FLAG_COMPACT: u16 = 0x0008;
+
+impl AndroidFile {
+    // 按文件头识别，dex 为 "dex\n" 加三位版本号，资源文件为块类型及固定的头长度
+    pub fn sniff(head: &[u8]) -> Option<AndroidFile> {
+        if head.len() >= 8 && head.starts_with(b"dex\n") && head[7] == 0 {
+            Some(AndroidFile::Dex)
+        } else if head.starts_with(&[0x03, 0x00, 0x08, 0x00]) {
+            Some(AndroidFile::BinaryXml)
+        } else if head.starts_with(&[0x02, 0x00, 0x0c, 0x00]) {
+            Some(AndroidFile::ResourceTable)
+        } else {
+            None
+        }
+    }
+
+    // 解析成便于匹配的文本，字符串表及资源每项一行
+    pub fn decode(self, data: &[u8]) -> io::Result<String> {
+        match self {
+            AndroidFile::Dex => dex_strings(data),
+            AndroidFile::BinaryXml => decode_binary_xml(data),
+            AndroidFile::ResourceTable => decode_resource_table(data),
+        }
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u8(data: &[u8], offset: usize) -> io::Result<u8> {
+    data.get(offset).copied().ok_or_else(|| invalid_data("文件不完整"))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> io::Result<u16> {
+    match data.get(offset..offset.saturating_add(2)) {
+        Some(bytes) => Ok(u16::from_le_bytes([bytes[0], bytes[1]])),
+        None => Err(invalid_data("文件不完整")),
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> io::Result<u32> {
+    match data.get(offset..offset.saturating_add(4)) {
+        Some(bytes) => Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(invalid_data("文件不完整")),
+    }
+}
+
+// offset 处 count 个 entry_size 字节的表是否完整，避免按损坏的计数循环
+fn table_fits(data: &[u8], offset: usize, count: usize, entry_size: usize) -> bool {
+    count.checked_mul(entry_size).and_then(|len| len.checked_add(offset)).is_some_and(|end| end <= data.len())
+}
+
+// 字符串中的换行转义掉，保证一项只占一行
+fn one_line(text: &str) -> String {
+    text.replace('\r', "\\r").replace('\n', "\\n")
+}
+
+// 读取 DEX 的 string_ids 表，每个字符串一行
+pub fn dex_strings(data: &[u8]) -> io::Result<String> {
+    let count = read_u32(data, 56)? as usize;
+    let ids_offset = read_u32(data, 60)? as usize;
+    if !table_fits(data, ids_offset, count, 4) {
+        return Err(invalid_data("DEX 字符串表越界"));
+    }
+    let mut output = String::new();
+    for i in 0..count {
+        let mut offset = read_u32(data, ids_offset + i * 4)? as usize;
+        // string_data_item 以 uleb128 编码的 UTF-16 长度开头，其后是以 0 结尾的 MUTF-8
+        while read_u8(data, offset)? & 0x80 != 0 {
+            offset += 1;
+        }
+        let start = offset + 1;
+        let end = data[start.min(data.len())..].iter().position(|&b| b == 0).map(|len| start + len).ok_or_else(|| invalid_data("DEX 字符串未结束"))?;
+        output.push_str(&one_line(&decode_mutf8(&data[start..end])));
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+// MUTF-8 与 UTF-8 的区别在于补充平面字符拆成两个代理项分别编码，先还原成 UTF-16 再转换
+fn decode_mutf8(bytes: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i] as u16;
+        let continuation = |n: usize| bytes.get(i + n).map_or(0, |&c| c as u16 & 0x3f);
+        if b < 0x80 {
+            units.push(b);
+            i += 1;
+        } else if b & 0xe0 == 0xc0 {
+            units.push((b & 0x1f) << 6 | continuation(1));
+            i += 2;
+        } else {
+            units.push((b & 0x0f) << 12 | continuation(1) << 6 | continuation(2));
+            i += 3;
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+// 取出 offset 处的块，返回块类型、头长度及整个块
+fn chunk_at(data: &[u8], offset: usize) -> io::Result<(u16, usize, &[u8])> {
+    let kind = read_u16(data, offset)?;
+    let header_size = read_u16(data, offset + 2)? as usize;
+    let size = read_u32(data, offset + 4)? as usize;
+    if size < 8 || header_size < 8 || header_size > size {
+        return Err(invalid_data("资源块长度不正确"));
+    }
+    let chunk = data.get(offset..offset.saturating_add(size)).ok_or_else(|| invalid_data("资源块越界"))?;
+    Ok((kind, header_size, chunk))
+}
+
+// 依次取出 data 中 header_size 之后的子块
+fn child_chunks(data: &[u8], header_size: usize) -> impl Iterator<Item = io::Result<(u16, usize, &[u8])>> {
+    let mut offset = header_size;
+    std::iter::from_fn(move || {
+        if offset >= data.len() {
+            return None;
+        }
+        let chunk = chunk_at(data, offset);
+        match &chunk {
+            Ok((_, _, bytes)) => offset += bytes.len(),
+            Err(_) => offset = data.len(),
+        }
+        Some(chunk)
+    })
+}
+
+// 解析 ResStringPool 块中的全部字符串
+fn parse_string_pool(chunk: &[u8], header_size: usize) -> io::Result<Vec<String>> {
+    let count = read_u32(chunk, 8)? as usize;
+    let flags = read_u32(chunk, 16)?;
+    let strings_start = read_u32(chunk, 20)? as usize;
+    if !table_fits(chunk, header_size, count, 4) {
+        return Err(invalid_data("字符串池越界"));
+    }
+    let mut strings = Vec::with_capacity(count);
+    for i in 0..count {
+        let offset = strings_start.saturating_add(read_u32(chunk, header_size + i * 4)? as usize);
+        let string = if flags & UTF8_FLAG != 0 {
+            // UTF-8 字符串前依次是 UTF-16 长度及字节长度，各占一或两个字节
+            let (_, offset) = read_utf8_len(chunk, offset)?;
+            let (len, offset) = read_utf8_len(chunk, offset)?;
+            let bytes = chunk.get(offset..offset + len).ok_or_else(|| invalid_data("字符串池越界"))?;
+            String::from_utf8_lossy(bytes).into_owned()
+        } else {
+            let mut len = read_u16(chunk, offset)? as usize;
+            let mut offset = offset + 2;
+            if len & 0x8000 != 0 {
+                len = (len & 0x7fff) << 16 | read_u16(chunk, offset)? as usize;
+                offset += 2;
+            }
+            let units = (0..len).map(|j| read_u16(chunk, offset + j * 2)).collect::<io::Result<Vec<u16>>>()?;
+            String::from_utf16_lossy(&units)
+        };
+        strings.push(string);
+    }
+    Ok(strings)
+}
+
+fn read_utf8_len(data: &[u8], offset: usize) -> io::Result<(usize, usize)> {
+    let first = read_u8(data, offset)? as usize;
+    if first & 0x80 != 0 {
+        Ok(((first & 0x7f) << 8 | read_u8(data, offset + 1)? as usize, offset + 2))
+    } else {
+        Ok((first, offset + 1))
+    }
+}
+
+fn pool_string(pool: &[String], index: u32) -> &str {
+    if index == NO_INDEX {
+        return "";
+    }
+    pool.get(index as usize).map_or("", |s| s.as_str())
+}
+
+// 按 Res_value 的类型格式化属性值
+fn typed_value(pool: &[String], data_type: u8, data: u32) -> String {
+    match data_type {
+        TYPE_NULL => String::new(),
+        TYPE_REFERENCE => format!("@0x{:08x}", data),
+        TYPE_ATTRIBUTE => format!("?0x{:08x}", data),
+        TYPE_STRING => pool_string(pool, data).to_string(),
+        TYPE_FLOAT => f32::from_bits(data).to_string(),
+        TYPE_INT_DEC => (data as i32).to_string(),
+        TYPE_INT_HEX => format!("0x{:x}", data),
+        TYPE_INT_BOOLEAN => (data != 0).to_string(),
+        0x1c..=0x1f => format!("#{:08x}", data),
+        _ => format!("0x{:08x}", data),
+    }
+}
+
+// 带命名空间前缀的元素或属性名，如 android:name
+fn qualified_name(pool: &[String], namespaces: &[(u32, u32)], ns: u32, name: u32) -> String {
+    match namespaces.iter().rev().find(|(_, uri)| *uri == ns) {
+        Some((prefix, _)) if ns != NO_INDEX => format!("{}:{}", pool_string(pool, *prefix), pool_string(pool, name)),
+        _ => pool_string(pool, name).to_string(),
+    }
+}
+
+// 把编译后的 XML 还原成文本，每个元素一行，属性与元素在同一行
+pub fn decode_binary_xml(data: &[u8]) -> io::Result<String> {
+    let (kind, header_size, root) = chunk_at(data, 0)?;
+    if kind != RES_XML_TYPE {
+        return Err(invalid_data("不是编译后的 XML"));
+    }
+    let mut pool = Vec::new();
+    let mut namespaces: Vec<(u32, u32)> = Vec::new();  // (前缀, uri)
+    let mut pending = Vec::new();  // 尚未写到元素上的命名空间声明
+    let mut depth = 0;
+    let mut output = String::new();
+    for chunk in child_chunks(root, header_size) {
+        let (kind, header_size, chunk) = chunk?;
+        let indent = "  ".repeat(depth);
+        match kind {
+            RES_STRING_POOL_TYPE => pool = parse_string_pool(chunk, header_size)?,
+            RES_XML_START_NAMESPACE_TYPE => {
+                let namespace = (read_u32(chunk, header_size)?, read_u32(chunk, header_size + 4)?);
+                namespaces.push(namespace);
+                pending.push(namespace);
+            }
+            RES_XML_END_NAMESPACE_TYPE => {
+                namespaces.pop();
+            }
+            RES_XML_START_ELEMENT_TYPE => {
+                let ext = header_size;
+                let mut line = format!("{}<{}", indent, qualified_name(&pool, &namespaces, read_u32(chunk, ext)?, read_u32(chunk, ext + 4)?));
+                for (prefix, uri) in pending.drain(..) {
+                    line.push_str(&format!(" xmlns:{}=\"{}\"", pool_string(&pool, prefix), pool_string(&pool, uri)));
+                }
+                let attribute_start = ext + read_u16(chunk, ext + 8)? as usize;
+                let attribute_size = read_u16(chunk, ext + 10)? as usize;
+                let attribute_count = read_u16(chunk, ext + 12)? as usize;
+                for i in 0..attribute_count {
+                    let at = attribute_start + i * attribute_size;
+                    let name = qualified_name(&pool, &namespaces, read_u32(chunk, at)?, read_u32(chunk, at + 4)?);
+                    // 有原始字符串时以原始字符串为准，否则按类型格式化
+                    let raw = read_u32(chunk, at + 8)?;
+                    let value = if raw != NO_INDEX {
+                        pool_string(&pool, raw).to_string()
+                    } else {
+                        typed_value(&pool, read_u8(chunk, at + 15)?, read_u32(chunk, at + 16)?)
+                    };
+                    line.push_str(&format!(" {}=\"{}\"", name, one_line(&value)));
+                }
+                output.push_str(&line);
+                output.push_str(">\n");
+                depth += 1;
+            }
+            RES_XML_END_ELEMENT_TYPE => {
+                depth = depth.saturating_sub(1);
+                let name = qualified_name(&pool, &namespaces, read_u32(chunk, header_size)?, read_u32(chunk, header_size + 4)?);
+                output.push_str(&format!("{}</{}>\n", "  ".repeat(depth), name));
+            }
+            RES_XML_CDATA_TYPE => {
+                let text = pool_string(&pool, read_u32(chunk, header_size)?);
+                output.push_str(&format!("{}{}\n", indent, one_line(text)));
+            }
+            // 资源 id 映射等与文本无关的块
+            _ => {}
+        }
+    }
+    Ok(output)
+}
+
+// 把 resources.arsc 中的字符串资源还原成 <string name="app_secret">...</string> 形式，每项一行，
+// 不同语言等配置下相同的资源只保留一行
+pub fn decode_resource_table(data: &[u8]) -> io::Result<String> {
+    let (kind, header_size, root) = chunk_at(data, 0)?;
+    if kind != RES_TABLE_TYPE {
+        return Err(invalid_data("不是 resources.arsc"));
+    }
+    let mut values = Vec::new();
+    let mut lines = Vec::new();
+    for chunk in child_chunks(root, header_size) {
+        let (kind, header_size, chunk) = chunk?;
+        match kind {
+            RES_STRING_POOL_TYPE => values = parse_string_pool(chunk, header_size)?,
+            RES_TABLE_PACKAGE_TYPE => decode_package(chunk, header_size, &values, &mut lines)?,
+            _ => {}
+        }
+    }
+    let mut seen = HashSet::new();
+    let mut output = String::new();
+    for line in lines {
+        if seen.insert(line.clone()) {
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    Ok(output)
+}
+
+// ResTable_package 头中类型名及资源名字符串池的位置
+const PACKAGE_TYPE_STRINGS: usize = 268;
+const PACKAGE_KEY_STRINGS: usize = 276;
+
+fn decode_package(package: &[u8], header_size: usize, values: &[String], lines: &mut Vec<String>) -> io::Result<()> {
+    let pool_at = |offset: usize| -> io::Result<Vec<String>> {
+        let (_, header_size, chunk) = chunk_at(package, offset)?;
+        parse_string_pool(chunk, header_size)
+    };
+    let type_names = pool_at(read_u32(package, PACKAGE_TYPE_STRINGS)? as usize)?;
+    let keys = pool_at(read_u32(package, PACKAGE_KEY_STRINGS)? as usize)?;
+    for chunk in child_chunks(package, header_size) {
+        let (kind, header_size, chunk) = chunk?;
+        if kind != RES_TABLE_TYPE_TYPE {
+            continue;
+        }
+        let type_name = pool_string(&type_names, (read_u8(chunk, 8)? as u32).wrapping_sub(1));
+        let flags = read_u8(chunk, 9)?;
+        let entry_count = read_u32(chunk, 12)? as usize;
+        let entries_start = read_u32(chunk, 16)? as usize;
+        if !table_fits(chunk, header_size, entry_count, 4) {
+            return Err(invalid_data("资源项越界"));
+        }
+        for i in 0..entry_count {
+            // 稀疏表为 (序号, 偏移/4) 对，16 位偏移以 0xffff 表示没有该项
+            let offset = if flags & FLAG_SPARSE != 0 {
+                read_u16(chunk, header_size + i * 4 + 2)? as usize * 4
+            } else if flags & FLAG_OFFSET16 != 0 {
+                match read_u16(chunk, header_size + i * 2)? {
+                    0xffff => continue,
+                    offset => offset as usize * 4,
+                }
+            } else {
+                match read_u32(chunk, header_size + i * 4)? {
+                    NO_INDEX => continue,
+                    offset => offset as usize,
+                }
+            };
+            let at = entries_start.saturating_add(offset);
+            let entry_flags = read_u16(chunk, at + 2)?;
+            let mut push = |key: u32, data_type: u8, data: u32| {
+                if data_type == TYPE_STRING {
+                    lines.push(format!("<{0} name=\"{1}\">{2}</{0}>", type_name, pool_string(&keys, key), one_line(pool_string(values, data))));
+                }
+            };
+            if entry_flags & FLAG_COMPACT != 0 {
+                // 紧凑项：键序号、标志（高 8 位为值类型）及数据
+                push(read_u16(chunk, at)? as u32, (entry_flags >> 8) as u8, read_u32(chunk, at + 4)?);
+            } else if entry_flags & FLAG_COMPLEX != 0 {
+                // 数组、样式等复合资源，逐个取出其中的字符串
+                let size = read_u16(chunk, at)? as usize;
+                let key = read_u32(chunk, at + 4)?;
+                let count = read_u32(chunk, at + 12)? as usize;
+                for j in 0..count.min(chunk.len() / 12) {
+                    let map = at + size + j * 12;
+                    push(key, read_u8(chunk, map + 7)?, read_u32(chunk, map + 8)?);
+                }
+            } else {
+                let size = read_u16(chunk, at)? as usize;
+                push(read_u32(chunk, at + 4)?, read_u8(chunk, at + size + 3)?, read_u32(chunk, at + size + 4)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    // 块头：类型、头长度、块长度，其后为头中的其余字段及内容
+    fn chunk(kind: u16, header: &[u8], body: &[u8]) -> Vec<u8> {
+        let header_size = 8 + header.len();
+        let mut bytes = u16s(&[kind, header_size as u16]);
+        bytes.extend(u32s(&[(header_size + body.len()) as u32]));
+        bytes.extend_from_slice(header);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    fn string_pool(strings: &[&str], utf8: bool) -> Vec<u8> {
+        let mut offsets = Vec::new();
+        let mut data = Vec::new();
+        for s in strings {
+            offsets.push(data.len() as u32);
+            if utf8 {
+                data.push(s.encode_utf16().count() as u8);
+                data.push(s.len() as u8);
+                data.extend_from_slice(s.as_bytes());
+                data.push(0);
+            } else {
+                let units: Vec<u16> = s.encode_utf16().collect();
+                data.extend(u16s(&[units.len() as u16]));
+                data.extend(u16s(&units));
+                data.extend([0, 0]);
+            }
+        }
+        data.resize(data.len().div_ceil(4) * 4, 0);
+        let flags = if utf8 { UTF8_FLAG } else { 0 };
+        let strings_start = 28 + offsets.len() as u32 * 4;
+        let header = u32s(&[strings.len() as u32, 0, flags, strings_start, 0]);
+        let mut body = u32s(&offsets);
+        body.extend(data);
+        chunk(RES_STRING_POOL_TYPE, &header, &body)
+    }
+
+    fn dex(strings: &[&[u8]]) -> Vec<u8> {
+        let mut header = vec![0u8; 0x70];
+        header[..8].copy_from_slice(b"dex\n035\0");
+        header[56..60].copy_from_slice(&(strings.len() as u32).to_le_bytes());
+        header[60..64].copy_from_slice(&0x70u32.to_le_bytes());
+        let mut ids = Vec::new();
+        let mut data = Vec::new();
+        let data_start = 0x70 + strings.len() * 4;
+        for s in strings {
+            ids.push((data_start + data.len()) as u32);
+            // uleb128 长度，大于 127 时占两个字节
+            data.extend([0x81, 0x01]);
+            data.extend_from_slice(s);
+            data.push(0);
+        }
+        header.extend(u32s(&ids));
+        header.extend(data);
+        header
+    }
+
+    #[test]
+    fn dex_strings_decode_mutf8() {
+        // 补充平面字符拆成代理项分别编码，NUL 编码为 C0 80
+        let emoji = [0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80];
+        let nul = [b'a', 0xc0, 0x80, b'b'];
+        let data = dex(&[b"password=DexSecret1", &emoji, &nul, b"multi\nline"]);
+        assert_eq!(AndroidFile::sniff(&data), Some(AndroidFile::Dex));
+        let text = AndroidFile::Dex.decode(&data).unwrap();
+        assert_eq!(text, "password=DexSecret1\n\u{1F600}\na\0b\nmulti\\nline\n");
+    }
+
+    #[test]
+    fn dex_rejects_broken_tables() {
+        let mut data = dex(&[b"abc"]);
+        data[56..60].copy_from_slice(&1000u32.to_le_bytes());
+        assert!(dex_strings(&data).is_err());
+        let mut data = dex(&[b"abc"]);
+        data.pop();  // 字符串没有结尾的 0
+        assert!(dex_strings(&data).is_err());
+        assert!(dex_strings(b"dex\n035\0").is_err());
+    }
+
+    fn xml_node(kind: u16, ext: &[u8]) -> Vec<u8> {
+        chunk(kind, &u32s(&[1, NO_INDEX]), ext)
+    }
+
+    fn attribute(ns: u32, name: u32, raw: u32, data_type: u8, data: u32) -> Vec<u8> {
+        let mut bytes = u32s(&[ns, name, raw]);
+        bytes.extend(u16s(&[8]));
+        bytes.extend([0, data_type]);
+        bytes.extend(u32s(&[data]));
+        bytes
+    }
+
+    fn binary_xml(utf8: bool) -> Vec<u8> {
+        let strings = ["android", "http://schemas.android.com/apk/res/android", "manifest", "package", "com.example.app", "meta-data", "value", "password=AxmlSecret1", "debuggable", "line1\nline2"];
+        let mut body = string_pool(&strings, utf8);
+        body.extend(xml_node(RES_XML_START_NAMESPACE_TYPE, &u32s(&[0, 1])));
+        let start = |name: u32, attributes: &[Vec<u8>]| {
+            let mut ext = u32s(&[NO_INDEX, name]);
+            ext.extend(u16s(&[20, 20, attributes.len() as u16, 0, 0, 0]));
+            ext.extend(attributes.concat());
+            xml_node(RES_XML_START_ELEMENT_TYPE, &ext)
+        };
+        body.extend(start(2, &[attribute(NO_INDEX, 3, 4, TYPE_STRING, 4)]));
+        body.extend(start(5, &[attribute(1, 6, NO_INDEX, TYPE_STRING, 7), attribute(1, 8, NO_INDEX, TYPE_INT_BOOLEAN, 1)]));
+        body.extend(xml_node(RES_XML_CDATA_TYPE, &u32s(&[9, 0, 0])));
+        body.extend(xml_node(RES_XML_END_ELEMENT_TYPE, &u32s(&[NO_INDEX, 5])));
+        body.extend(xml_node(RES_XML_END_ELEMENT_TYPE, &u32s(&[NO_INDEX, 2])));
+        body.extend(xml_node(RES_XML_END_NAMESPACE_TYPE, &u32s(&[0, 1])));
+        chunk(RES_XML_TYPE, &[], &body)
+    }
+
+    #[test]
+    fn binary_xml_decodes_elements_and_attributes() {
+        let expected = "<manifest xmlns:android=\"http://schemas.android.com/apk/res/android\" package=\"com.example.app\">\n\
+                        \x20 <meta-data android:value=\"password=AxmlSecret1\" android:debuggable=\"true\">\n\
+                        \x20   line1\\nline2\n\
+                        \x20 </meta-data>\n\
+                        </manifest>\n";
+        for utf8 in [false, true] {
+            let data = binary_xml(utf8);
+            assert_eq!(AndroidFile::sniff(&data), Some(AndroidFile::BinaryXml));
+            assert_eq!(AndroidFile::BinaryXml.decode(&data).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn binary_xml_rejects_broken_chunks() {
+        assert!(decode_binary_xml(&[0x03, 0x00, 0x08, 0x00, 0xff, 0xff, 0xff, 0xff]).is_err());
+        let mut data = binary_xml(false);
+        data.truncate(data.len() - 4);
+        assert!(decode_binary_xml(&data).is_err());
+    }
+
+    fn table_type(type_id: u8, flags: u8, offsets: &[u32], entries: &[u8]) -> Vec<u8> {
+        let table: Vec<u8> = if flags & FLAG_OFFSET16 != 0 {
+            u16s(&offsets.iter().map(|o| if *o == NO_INDEX { 0xffff } else { (*o / 4) as u16 }).collect::<Vec<_>>())
+        } else {
+            u32s(offsets)
+        };
+        let header_size = 20 + 64;
+        let mut header = vec![type_id, flags, 0, 0];
+        header.extend(u32s(&[offsets.len() as u32, (header_size + table.len()) as u32, 64]));
+        header.resize(header_size - 8, 0);
+        let mut body = table;
+        body.extend_from_slice(entries);
+        chunk(RES_TABLE_TYPE_TYPE, &header, &body)
+    }
+
+    fn simple_entry(key: u32, data_type: u8, data: u32) -> Vec<u8> {
+        let mut bytes = u16s(&[8, 0]);
+        bytes.extend(u32s(&[key]));
+        bytes.extend(u16s(&[8]));
+        bytes.extend([0, data_type]);
+        bytes.extend(u32s(&[data]));
+        bytes
+    }
+
+    fn resource_table() -> Vec<u8> {
+        let type_names = string_pool(&["string"], false);
+        let keys = string_pool(&["app_secret", "greet", "compact"], true);
+        let mut entries = simple_entry(0, TYPE_STRING, 0);
+        entries.extend(simple_entry(1, TYPE_STRING, 1));
+        let mut types = table_type(1, 0, &[0, 16], &entries);
+        // 其他语言下的相同值只输出一次，0xffff 表示没有该项
+        types.extend(table_type(1, FLAG_OFFSET16, &[0, NO_INDEX], &simple_entry(0, TYPE_STRING, 0)));
+        let mut compact = u16s(&[2, FLAG_COMPACT | (TYPE_STRING as u16) << 8]);
+        compact.extend(u32s(&[2]));
+        types.extend(table_type(1, 0, &[NO_INDEX, NO_INDEX, 0], &compact));
+
+        let header_size = 288;
+        let mut header = u32s(&[0x7f]);
+        header.resize(header_size - 8 - 20, 0);
+        header.extend(u32s(&[header_size as u32, 0, (header_size + type_names.len()) as u32, 0, 0]));
+        let mut body = type_names;
+        body.extend(keys);
+        body.extend(types);
+        let mut root = string_pool(&["password=ArscSecret1", "hello\nworld", "compact value"], true);
+        root.extend(chunk(RES_TABLE_PACKAGE_TYPE, &header, &body));
+        chunk(RES_TABLE_TYPE, &u32s(&[1]), &root)
+    }
+
+    #[test]
+    fn resource_table_decodes_string_resources() {
+        let data = resource_table();
+        assert_eq!(AndroidFile::sniff(&data), Some(AndroidFile::ResourceTable));
+        assert_eq!(
+            AndroidFile::ResourceTable.decode(&data).unwrap(),
+            "<string name=\"app_secret\">password=ArscSecret1</string>\n\
+             <string name=\"greet\">hello\\nworld</string>\n\
+             <string name=\"compact\">compact value</string>\n"
+        );
+    }
+
+    #[test]
+    fn resource_table_rejects_broken_chunks() {
+        let mut data = resource_table();
+        data.truncate(data.len() - 8);
+        assert!(decode_resource_table(&data).is_err());
+        assert!(decode_resource_table(&binary_xml(true)).is_err());
+    }
+}
@@ -0,0 +1,810 @@
+// 压缩包遍历时共用的解压、解析及文本解码工具
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bzip2::write::BzEncoder;
+use encoding_rs::GBK;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::android::AndroidFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Gbk,
+}
+
+// 按 UTF-8 解码，失败时尝试 GBK，都失败则认为不是文本文件
+pub fn decode_text(bytes: &[u8]) -> Option<(String, TextEncoding)> {
+    match std::str::from_utf8(bytes) {
+        Ok(c) => Some((c.to_string(), TextEncoding::Utf8)),
+        Err(_) => {
+            let (cow, _, had_errors) = GBK.decode(bytes);
+            if had_errors {
+                None
+            } else {
+                Some((cow.into_owned(), TextEncoding::Gbk))
+            }
+        }
+    }
+}
+
+// 按原编码写回文本
+pub fn encode_text(contents: &str, encoding: TextEncoding) -> Vec<u8> {
+    match encoding {
+        TextEncoding::Utf8 => contents.as_bytes().to_vec(),
+        TextEncoding::Gbk => GBK.encode(contents).0.into_owned(),
+    }
+}
+
+// 压缩包路径与其中条目名之间的分隔符，如 outer.war!/WEB-INF/lib/x.jar!/com/A.class，
+// 可以区分压缩包中的目录与嵌套的压缩包
+pub const ENTRY_SEPARATOR: &str = "!/";
+
+pub fn entry_path(archive_path: &str, entry_name: &str) -> String {
+    format!("{}{}{}", archive_path, ENTRY_SEPARATOR, entry_name)
+}
+
+// 压缩包内文件名可能是 GBK 编码
+pub fn decode_entry_name(raw: &[u8]) -> String {
+    let (decoded_name, _, _) = GBK.decode(raw);
+    decoded_name.to_string()
+}
+
+// 单文件压缩格式，解压后可能是文本、tar 或其他压缩包
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Xz,
+    Bzip2,
+    Zstd,
+    Lz4,
+}
+
+// (后缀, 压缩格式, 解压后是否为 tar)
+const COMPRESSED_SUFFIXES: &[(&str, Compression, bool)] = &[
+    (".gz", Compression::Gzip, false),
+    (".tgz", Compression::Gzip, true),
+    (".xz", Compression::Xz, false),
+    (".txz", Compression::Xz, true),
+    (".bz2", Compression::Bzip2, false),
+    (".tbz2", Compression::Bzip2, true),
+    (".tbz", Compression::Bzip2, true),
+    (".zst", Compression::Zstd, false),
+    (".tzst", Compression::Zstd, true),
+    (".lz4", Compression::Lz4, false),
+];
+
+impl Compression {
+    // 按后缀识别压缩格式，同时判断解压后是否为 tar（如 .tar.xz、.tbz2）
+    pub fn from_file_name(file_name: &str) -> Option<(Compression, bool)> {
+        COMPRESSED_SUFFIXES.iter().find_map(|(suffix, compression, is_tar)| {
+            let stem = file_name.strip_suffix(suffix)?;
+            Some((*compression, *is_tar || stem.ends_with(".tar")))
+        })
+    }
+
+    // 最多解压出 limit + 1 字节，调用方据此判断是否超出大小限制
+    pub fn decompress(self, bytes: &[u8], limit: u64) -> io::Result<Vec<u8>> {
+        let max = limit.saturating_add(1);
+        let mut decompressed_data = Vec::new();
+        match self {
+            Compression::Gzip => {
+                GzDecoder::new(bytes).take(max).read_to_end(&mut decompressed_data)?;
+            }
+            Compression::Xz => {
+                let mut output = LimitedWriter { data: Vec::new(), limit: max };
+                let result = lzma_rs::xz_decompress(&mut &bytes[..], &mut output);
+                decompressed_data = output.data;
+                // 输出写满后 lzma-rs 同样返回错误，此时已超出限制，不算解压失败
+                if let Err(e) = result {
+                    if (decompressed_data.len() as u64) < max {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)));
+                    }
+                }
+            }
+            Compression::Bzip2 => {
+                bzip2_rs::DecoderReader::new(bytes).take(max).read_to_end(&mut decompressed_data)?;
+            }
+            Compression::Zstd => {
+                // 文件可能由多个 zstd 帧拼接而成
+                let mut input = bytes;
+                while !input.is_empty() && (decompressed_data.len() as u64) < max {
+                    let decoder = ruzstd::decoding::StreamingDecoder::new(&mut input)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+                    decoder.take(max - decompressed_data.len() as u64).read_to_end(&mut decompressed_data)?;
+                }
+            }
+            Compression::Lz4 => {
+                lz4_flex::frame::FrameDecoder::new(bytes).take(max).read_to_end(&mut decompressed_data)?;
+            }
+        }
+        Ok(decompressed_data)
+    }
+
+    // 按同一格式重新压缩，用于脱敏后写回
+    pub fn compress(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            Compression::Xz => {
+                let mut output = Vec::new();
+                lzma_rs::xz_compress(&mut &bytes[..], &mut output)?;
+                Ok(output)
+            }
+            Compression::Bzip2 => {
+                let mut encoder = BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            Compression::Zstd => Ok(ruzstd::encoding::compress_to_vec(bytes, ruzstd::encoding::CompressionLevel::Fastest)),
+            Compression::Lz4 => {
+                let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+                encoder.write_all(bytes)?;
+                encoder.finish().map_err(io::Error::other)
+            }
+        }
+    }
+}
+
+// 写满 limit 字节后返回错误，用于中止只能输出到 Write 的解压
+struct LimitedWriter {
+    data: Vec<u8>,
+    limit: u64,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = (self.limit - self.data.len() as u64).min(buf.len() as u64) as usize;
+        if room == 0 && !buf.is_empty() {
+            return Err(io::Error::other("超出解压大小限制"));
+        }
+        self.data.extend_from_slice(&buf[..room]);
+        Ok(room)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// 压缩包及需要特殊处理的文件格式，不属于其中的按文本处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Zip,  // 含 jar、war
+    SevenZ,
+    Tar,
+    Class,
+    Compressed(Compression),
+    Android(AndroidFile),  // APK/AAR 中的 dex、编译后的 XML 及 resources.arsc
+}
+
+// 识别格式需要读取的文件头长度，tar 的 ustar 标识位于 257 字节处
+pub const SNIFF_LEN: usize = 512;
+
+// 按文件头识别格式
+pub fn sniff_format(head: &[u8]) -> Option<Format> {
+    let format = if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+        Format::Zip
+    } else if head.starts_with(&[0x37, 0x7a, 0xbc, 0xaf, 0x27, 0x1c]) {
+        Format::SevenZ
+    } else if head.starts_with(&[0x1f, 0x8b]) {
+        Format::Compressed(Compression::Gzip)
+    } else if head.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        Format::Compressed(Compression::Xz)
+    } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Format::Compressed(Compression::Zstd)
+    } else if head.starts_with(&[0x04, 0x22, 0x4d, 0x18]) {
+        Format::Compressed(Compression::Lz4)
+    } else if head.len() >= 4 && head.starts_with(b"BZh") && (b'1'..=b'9').contains(&head[3]) {
+        Format::Compressed(Compression::Bzip2)
+    } else if head.starts_with(&[0xca, 0xfe, 0xba, 0xbe]) && head.len() >= 8 && u16::from_be_bytes([head[6], head[7]]) >= 45 {
+        // Mach-O 通用二进制也以 CAFEBABE 开头，其后是架构数，按 class 主版本号（不小于 45）区分
+        Format::Class
+    } else if let Some(file) = AndroidFile::sniff(head) {
+        Format::Android(file)
+    } else if head.len() >= 262 && &head[257..262] == b"ustar" {
+        Format::Tar
+    } else {
+        return None;
+    };
+    Some(format)
+}
+
+// 以文件内容为准，后缀只作参考：没有 ustar 标识的旧式 tar 及文件头不完整的 class 按后缀判断，
+// 其余格式都有固定的文件头，后缀与内容不符时（如改名为 .zip 的文本）按文本处理
+pub fn detect_format(head: &[u8], file_name: &str) -> Option<Format> {
+    sniff_format(head).or_else(|| {
+        if file_name.ends_with(".tar") {
+            Some(Format::Tar)
+        } else if file_name.ends_with(".class") {
+            Some(Format::Class)
+        } else {
+            None
+        }
+    })
+}
+
+// 解压后的内容识别不出时，按 .tar.gz、.tgz 等文件名判断是否为 tar
+pub fn detect_decompressed_format(data: &[u8], file_name: &str) -> Option<Format> {
+    sniff_format(data).or_else(|| match Compression::from_file_name(file_name) {
+        Some((_, true)) => Some(Format::Tar),
+        _ => None,
+    })
+}
+
+// 临时文件名序号，同一进程中多个线程同时溢出时不重名
+static TEMP_FILE_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+// 系统临时目录中的可读写文件，drop 时删除
+pub struct TempFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl TempFile {
+    pub fn create() -> io::Result<TempFile> {
+        let path = std::env::temp_dir().join(format!(
+            "minigrep-{}-{}.tmp",
+            std::process::id(),
+            TEMP_FILE_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = fs::OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        Ok(TempFile { path, file })
+    }
+}
+
+impl Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Seek for TempFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// 测试用的临时目录，drop 时连同其中的文件删除
+#[cfg(test)]
+pub(crate) struct TempDir {
+    pub path: PathBuf,
+}
+
+#[cfg(test)]
+impl TempDir {
+    pub fn create() -> TempDir {
+        let path = std::env::temp_dir().join(format!(
+            "minigrep-{}-{}",
+            std::process::id(),
+            TEMP_FILE_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    // 写入相对路径处的文件，上级目录不存在时创建
+    pub fn write(&self, relative: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// 测试用的 zip 压缩包，条目按默认方式压缩
+#[cfg(test)]
+pub(crate) fn zip_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    use std::io::{Cursor, Write};
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in entries {
+        writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+// 读出的嵌套压缩包：不超过限制时在内存中，否则在临时文件中
+pub enum Spooled {
+    Memory(Cursor<Vec<u8>>),
+    File(TempFile),
+}
+
+impl Read for Spooled {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Spooled::Memory(cursor) => cursor.read(buf),
+            Spooled::File(file) => file.read(buf),
+        }
+    }
+}
+
+impl Seek for Spooled {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Spooled::Memory(cursor) => cursor.seek(pos),
+            Spooled::File(file) => file.seek(pos),
+        }
+    }
+}
+
+// 读出全部内容，超过 limit 字节时转存到临时文件
+pub fn spool(reader: &mut dyn Read, limit: u64) -> io::Result<Spooled> {
+    let mut buffer = Vec::new();
+    (&mut *reader).take(limit + 1).read_to_end(&mut buffer)?;
+    if buffer.len() as u64 <= limit {
+        return Ok(Spooled::Memory(Cursor::new(buffer)));
+    }
+    let mut file = TempFile::create()?;
+    file.write_all(&buffer)?;
+    drop(buffer);
+    io::copy(reader, &mut file)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(Spooled::File(file))
+}
+
+pub const TAR_BLOCK_SIZE: usize = 512;
+// GNU 长文件名、PAX 扩展头的内容需要读入内存，超过该大小视为格式错误
+const TAR_EXTENSION_LIMIT: u64 = 1024 * 1024;
+
+// tar 中的一个条目
+#[derive(Debug, Clone)]
+pub struct TarEntry {
+    pub name: String,
+    pub size: u64,
+    pub is_file: bool,              // 目录、链接、设备等非普通文件为 false
+    pub size_from_pax: bool,        // 大小记录在 PAX 扩展头中
+    pub extension_headers: Vec<u8>, // 条目之前的 GNU 长文件名、PAX 扩展头，含其内容及补齐
+    pub header: Vec<u8>,            // 条目自身的头部块
+}
+
+// 从 reader 中按 ustar、GNU 及 PAX 格式依次读取 tar 条目，不整个读入内存；遇到全零块结束，出错后不再继续
+pub struct TarReader<R> {
+    reader: R,
+    remaining: u64,  // 当前条目尚未读取的内容
+    padding: u64,    // 当前条目内容之后的补齐
+    finished: bool,
+}
+
+impl<R: Read> TarReader<R> {
+    pub fn new(reader: R) -> TarReader<R> {
+        TarReader { reader, remaining: 0, padding: 0, finished: false }
+    }
+
+    // 读取下一个条目的头部，上一个条目没有读完的内容直接跳过
+    pub fn next_entry(&mut self) -> io::Result<Option<TarEntry>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let entry = self.read_entry();
+        if !matches!(entry, Ok(Some(_))) {
+            self.finished = true;
+        }
+        entry
+    }
+
+    // 当前条目的内容，只能按顺序读取一次
+    pub fn entry_data(&mut self) -> TarEntryData<'_, R> {
+        TarEntryData { tar: self }
+    }
+
+    fn read_entry(&mut self) -> io::Result<Option<TarEntry>> {
+        io::copy(&mut self.entry_data(), &mut io::sink())?;
+        // 归档结尾的补齐可能被截掉，此时下一个头部读不满，按结束处理
+        io::copy(&mut (&mut self.reader).take(self.padding), &mut io::sink())?;
+        self.padding = 0;
+
+        let mut extension_headers = Vec::new();
+        let mut long_name = None;
+        let mut pax_path = None;
+        let mut pax_size = None;
+        loop {
+            let mut block = Vec::with_capacity(TAR_BLOCK_SIZE);
+            (&mut self.reader).take(TAR_BLOCK_SIZE as u64).read_to_end(&mut block)?;
+            if block.len() < TAR_BLOCK_SIZE || block.iter().all(|b| *b == 0) {
+                return Ok(None);
+            }
+            if !tar_checksum_ok(&block) {
+                return Err(invalid_tar("tar 头部校验和错误"));
+            }
+            let type_flag = block[156];
+            let size = match parse_tar_number(&block[124..136]) {
+                // PAX 扩展头中的大小只作用于其后的条目
+                Some(size) if matches!(type_flag, b'x' | b'g' | b'L' | b'K') => size,
+                Some(size) => pax_size.unwrap_or(size),
+                None => return Err(invalid_tar("tar 条目大小无法解析")),
+            };
+            if !matches!(type_flag, b'x' | b'g' | b'L' | b'K') {
+                let name = pax_path.or(long_name).unwrap_or_else(|| tar_header_name(&block));
+                let is_file = matches!(type_flag, 0 | b'0' | b'7') && !name.ends_with('/');
+                self.remaining = size;
+                self.padding = tar_padding(size);
+                return Ok(Some(TarEntry {
+                    name: name.trim_start_matches("./").to_string(),
+                    size,
+                    is_file,
+                    size_from_pax: pax_size.is_some(),
+                    extension_headers,
+                    header: block,
+                }));
+            }
+
+            if size > TAR_EXTENSION_LIMIT {
+                return Err(invalid_tar("tar 扩展头过大"));
+            }
+            let mut data = vec![0; size as usize];
+            self.reader.read_exact(&mut data).map_err(|_| invalid_tar("tar 文件已截断，条目不完整"))?;
+            match type_flag {
+                // GNU 长文件名，内容为其后条目的完整路径
+                b'L' => long_name = Some(decode_tar_name(trim_nul(&data))),
+                b'x' => {
+                    for (key, value) in parse_pax_records(&data) {
+                        match key {
+                            "path" => pax_path = Some(value.to_string()),
+                            "size" => {
+                                pax_size = Some(value.parse().map_err(|_| invalid_tar("PAX 扩展头中的大小无法解析"))?)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                // 全局 PAX 扩展头及 GNU 长链接目标与条目内容无关
+                _ => {}
+            }
+            extension_headers.extend_from_slice(&block);
+            extension_headers.extend_from_slice(&data);
+            (&mut self.reader).take(tar_padding(size)).read_to_end(&mut extension_headers)?;
+        }
+    }
+}
+
+// tar 条目的内容，读完后返回 0，归档在内容中间结束时返回错误
+pub struct TarEntryData<'a, R> {
+    tar: &'a mut TarReader<R>,
+}
+
+impl<R: Read> Read for TarEntryData<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.tar.remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        let len = buf.len().min(usize::try_from(self.tar.remaining).unwrap_or(usize::MAX));
+        let read = self.tar.reader.read(&mut buf[..len])?;
+        if read == 0 {
+            return Err(invalid_tar("tar 文件已截断，条目不完整"));
+        }
+        self.tar.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+fn invalid_tar(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// 字段以 NUL 结尾，未写满时后面补 NUL
+fn trim_nul(field: &[u8]) -> &[u8] {
+    &field[..field.iter().position(|b| *b == 0).unwrap_or(field.len())]
+}
+
+// 文件名优先按 UTF-8 解码，否则按 GBK（Windows 上打包的 tar）
+fn decode_tar_name(raw: &[u8]) -> String {
+    match std::str::from_utf8(raw) {
+        Ok(name) => name.to_string(),
+        Err(_) => decode_entry_name(raw),
+    }
+}
+
+// POSIX ustar 格式的长路径分为前缀和文件名两段，GNU 格式的同一位置存放其他信息
+fn tar_header_name(block: &[u8]) -> String {
+    let name = trim_nul(&block[0..100]);
+    let prefix = trim_nul(&block[345..500]);
+    if &block[257..263] == b"ustar\0" && !prefix.is_empty() {
+        let mut path = prefix.to_vec();
+        path.push(b'/');
+        path.extend_from_slice(name);
+        decode_tar_name(&path)
+    } else {
+        decode_tar_name(name)
+    }
+}
+
+// 数字字段为八进制文本；首字节最高位为 1 时为 GNU 的 base-256 编码，用于超过 8GB 的大小
+fn parse_tar_number(field: &[u8]) -> Option<u64> {
+    if field[0] & 0x80 != 0 {
+        if field[0] & 0x40 != 0 {
+            return None; // 负数
+        }
+        let mut value: u64 = u64::from(field[0] & 0x3f);
+        for b in &field[1..] {
+            value = value.checked_mul(256)?.checked_add(u64::from(*b))?;
+        }
+        return Some(value);
+    }
+    let text = std::str::from_utf8(trim_nul(field)).ok()?.trim_matches(' ');
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+// 校验和按校验和字段为空格时各字节之和计算，旧版本的 tar 按有符号字节计算
+fn tar_checksum_ok(block: &[u8]) -> bool {
+    let stored = match parse_tar_number(&block[148..156]) {
+        Some(stored) => stored,
+        None => return false,
+    };
+    let (mut unsigned, mut signed) = (0u64, 0i64);
+    for (i, b) in block[..TAR_BLOCK_SIZE].iter().enumerate() {
+        let b = if (148..156).contains(&i) { b' ' } else { *b };
+        unsigned += u64::from(b);
+        signed += i64::from(b as i8);
+    }
+    stored == unsigned || stored as i64 == signed
+}
+
+// PAX 扩展头由 "长度 键=值\n" 形式的记录组成，长度包含整条记录
+fn parse_pax_records(data: &[u8]) -> Vec<(&str, &str)> {
+    let mut records = Vec::new();
+    let mut rest = data;
+    while let Some(space) = rest.iter().position(|b| *b == b' ') {
+        let len = match std::str::from_utf8(&rest[..space]).ok().and_then(|len| len.parse::<usize>().ok()) {
+            Some(len) if len > space + 1 && len <= rest.len() => len,
+            _ => break,
+        };
+        let record = &rest[space + 1..len];
+        let record = record.strip_suffix(b"\n").unwrap_or(record);
+        if let Some((key, value)) = std::str::from_utf8(record).ok().and_then(|r| r.split_once('=')) {
+            records.push((key, value));
+        }
+        rest = &rest[len..];
+    }
+    records
+}
+
+// 文件内容后需要补齐到 512 字节的长度
+pub fn tar_padding(size: u64) -> u64 {
+    let block = TAR_BLOCK_SIZE as u64;
+    (block - size % block) % block
+}
+
+// 修改头部块中的文件大小并重新计算校验和，超出八进制字段范围时使用 base-256 编码
+pub fn set_tar_header_size(block: &mut [u8], size: usize) {
+    if (size as u64) < 1 << 33 {
+        let size_field = format!("{:011o}\0", size);
+        block[124..136].copy_from_slice(size_field.as_bytes());
+    } else {
+        block[124..128].copy_from_slice(&[0x80, 0, 0, 0]);
+        block[128..136].copy_from_slice(&(size as u64).to_be_bytes());
+    }
+    block[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = block[..TAR_BLOCK_SIZE].iter().map(|b| *b as u32).sum();
+    let checksum_field = format!("{:06o}\0 ", checksum);
+    block[148..156].copy_from_slice(checksum_field.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 构造 tar 头部块，name 超过 100 字节时按 ustar 前缀拆分
+    fn header(name: &str, size: u64, type_flag: u8) -> Vec<u8> {
+        let mut block = vec![0u8; TAR_BLOCK_SIZE];
+        let (prefix, name) = if name.len() > 100 { name.rsplit_once('/').unwrap() } else { ("", name) };
+        block[..name.len()].copy_from_slice(name.as_bytes());
+        block[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+        block[100..108].copy_from_slice(b"0000644\0");
+        block[156] = type_flag;
+        block[257..263].copy_from_slice(b"ustar\0");
+        block[263..265].copy_from_slice(b"00");
+        set_tar_header_size(&mut block, size as usize);
+        block
+    }
+
+    fn entry(name: &str, type_flag: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = header(name, data.len() as u64, type_flag);
+        bytes.extend_from_slice(data);
+        bytes.resize(bytes.len() + tar_padding(data.len() as u64) as usize, 0);
+        bytes
+    }
+
+    fn read_all(bytes: &[u8]) -> io::Result<Vec<(String, bool, Vec<u8>)>> {
+        let mut tar = TarReader::new(bytes);
+        let mut entries = Vec::new();
+        while let Some(entry) = tar.next_entry()? {
+            let mut data = Vec::new();
+            tar.entry_data().read_to_end(&mut data)?;
+            entries.push((entry.name, entry.is_file, data));
+        }
+        Ok(entries)
+    }
+
+    #[test]
+    fn tar_padding_boundaries() {
+        assert_eq!(tar_padding(0), 0);
+        assert_eq!(tar_padding(1), 511);
+        assert_eq!(tar_padding(512), 0);
+        assert_eq!(tar_padding(513), 511);
+    }
+
+    #[test]
+    fn tar_number_formats() {
+        assert_eq!(parse_tar_number(b"00000000017\0"), Some(15));
+        assert_eq!(parse_tar_number(b"     17     "), Some(15));
+        assert_eq!(parse_tar_number(b"\0\0\0\0\0\0\0\0\0\0\0\0"), Some(0));
+        assert_eq!(parse_tar_number(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x00]), Some(256));
+        // base-256 的负数及非八进制字符
+        assert_eq!(parse_tar_number(&[0xff; 12]), None);
+        assert_eq!(parse_tar_number(b"0000000009\0\0"), None);
+    }
+
+    #[test]
+    fn tar_header_size_uses_base256_for_large_entries() {
+        let mut block = header("big.bin", 0, b'0');
+        set_tar_header_size(&mut block, 1 << 33);
+        assert_eq!(block[124], 0x80);
+        assert_eq!(parse_tar_number(&block[124..136]), Some(1 << 33));
+        assert!(tar_checksum_ok(&block));
+        set_tar_header_size(&mut block, (1 << 33) - 1);
+        assert_eq!(&block[124..136], b"77777777777\0");
+        assert!(tar_checksum_ok(&block));
+    }
+
+    #[test]
+    fn tar_checksum_accepts_signed_sum() {
+        let mut block = header("\u{4e2d}.txt", 0, b'0');
+        block[148..156].copy_from_slice(b"        ");
+        let signed: i64 = block.iter().map(|b| i64::from(*b as i8)).sum();
+        block[148..156].copy_from_slice(format!("{:06o}\0 ", signed).as_bytes());
+        assert!(tar_checksum_ok(&block));
+        block[0] ^= 1;
+        assert!(!tar_checksum_ok(&block));
+    }
+
+    #[test]
+    fn tar_reads_ustar_gnu_and_pax_names() {
+        let long = format!("{}/{}.txt", "d".repeat(120), "f".repeat(80));
+        let prefixed = format!("{}/{}.txt", "p".repeat(90), "q".repeat(60));
+        let mut bytes = entry("./dir/", b'5', b"");
+        bytes.extend(entry("././@LongLink", b'L', format!("{}\0", long).as_bytes()));
+        bytes.extend(entry("truncated-name", b'0', b"gnu"));
+        bytes.extend(entry(&prefixed, b'0', b"ustar"));
+        bytes.extend(entry("PaxHeader", b'x', b"23 path=pax/\xe4\xb8\xad\xe6\x96\x87.txt\n"));
+        bytes.extend(entry("short", b'0', b"pax"));
+        bytes.extend(entry("link", b'2', b""));
+        bytes.extend([0u8; 1024]);
+        let entries = read_all(&bytes).unwrap();
+        let names: Vec<(&str, bool)> = entries.iter().map(|(name, is_file, _)| (name.as_str(), *is_file)).collect();
+        assert_eq!(names, vec![("dir/", false), (long.as_str(), true), (prefixed.as_str(), true), ("pax/中文.txt", true), ("link", false)]);
+        assert_eq!(entries[1].2, b"gnu");
+        assert_eq!(entries[2].2, b"ustar");
+        assert_eq!(entries[3].2, b"pax");
+    }
+
+    #[test]
+    fn tar_pax_size_overrides_header() {
+        let data = vec![b'x'; 600];
+        let mut bytes = entry("PaxHeader", b'x', b"12 size=600\n");
+        let mut own = header("big.txt", 0, b'0');
+        set_tar_header_size(&mut own, 0);
+        bytes.extend(own);
+        bytes.extend(&data);
+        bytes.resize(bytes.len() + tar_padding(600) as usize, 0);
+        bytes.extend(entry("after.txt", b'0', b"after"));
+        let mut tar = TarReader::new(&bytes[..]);
+        let first = tar.next_entry().unwrap().unwrap();
+        assert!(first.size_from_pax);
+        assert_eq!(first.size, 600);
+        assert_eq!(first.extension_headers.len(), 2 * TAR_BLOCK_SIZE);
+        // 没有读的内容在读取下一个条目时跳过
+        let second = tar.next_entry().unwrap().unwrap();
+        assert_eq!(second.name, "after.txt");
+        assert!(!second.size_from_pax);
+    }
+
+    #[test]
+    fn tar_end_and_truncation() {
+        // 没有结尾空块或最后一个块不完整时按结束处理
+        let mut bytes = entry("a.txt", b'0', b"a");
+        assert_eq!(read_all(&bytes).unwrap().len(), 1);
+        bytes.extend([0u8; 100]);
+        assert_eq!(read_all(&bytes).unwrap().len(), 1);
+
+        let bytes = entry("b.txt", b'0', &[b'b'; 1000]);
+        let err = read_all(&bytes[..TAR_BLOCK_SIZE + 100]).unwrap_err();
+        assert!(err.to_string().contains("截断"));
+
+        let mut bytes = entry("c.txt", b'0', b"c");
+        bytes[0] ^= 1;
+        let mut tar = TarReader::new(&bytes[..]);
+        assert!(tar.next_entry().is_err());
+        // 出错后不再继续
+        assert!(tar.next_entry().unwrap().is_none());
+    }
+
+    #[test]
+    fn sniff_and_detect_formats() {
+        let tar = entry("a.txt", b'0', b"a");
+        assert_eq!(sniff_format(&tar), Some(Format::Tar));
+        let mut old_tar = tar.clone();
+        old_tar[257..265].fill(0);
+        assert_eq!(sniff_format(&old_tar), None);
+        assert_eq!(detect_format(&old_tar, "old.tar"), Some(Format::Tar));
+        assert_eq!(sniff_format(b"PK\x03\x04"), Some(Format::Zip));
+        assert_eq!(sniff_format(b"BZh9"), Some(Format::Compressed(Compression::Bzip2)));
+        assert_eq!(sniff_format(b"BZh0"), None);
+        // Mach-O 通用二进制与 class 同样以 CAFEBABE 开头
+        assert_eq!(sniff_format(&[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52]), Some(Format::Class));
+        assert_eq!(sniff_format(&[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 2]), None);
+        assert_eq!(detect_format(b"plain text", "renamed.zip"), None);
+        assert_eq!(Compression::from_file_name("logs.tbz2"), Some((Compression::Bzip2, true)));
+        assert_eq!(Compression::from_file_name("app.log.gz"), Some((Compression::Gzip, false)));
+    }
+
+    #[test]
+    fn compression_round_trip() {
+        let data = "password=secret\n".repeat(1000).into_bytes();
+        for compression in [Compression::Gzip, Compression::Xz, Compression::Bzip2, Compression::Zstd, Compression::Lz4] {
+            let compressed = compression.compress(&data).unwrap();
+            assert_eq!(sniff_format(&compressed), Some(Format::Compressed(compression)));
+            assert_eq!(compression.decompress(&compressed, u64::MAX).unwrap(), data);
+            // 最多解压出 limit + 1 字节
+            assert_eq!(compression.decompress(&compressed, 100).unwrap().len(), 101, "{:?}", compression);
+        }
+    }
+
+    #[test]
+    fn spool_moves_large_content_to_temp_file() {
+        let data = vec![7u8; 100];
+        let mut small = spool(&mut &data[..], 100).unwrap();
+        assert!(matches!(small, Spooled::Memory(_)));
+        let mut large = spool(&mut &data[..], 10).unwrap();
+        assert!(matches!(large, Spooled::File(_)));
+        for spooled in [&mut small, &mut large] {
+            let mut read = Vec::new();
+            spooled.read_to_end(&mut read).unwrap();
+            assert_eq!(read, data);
+        }
+    }
+
+    #[test]
+    fn text_encodings() {
+        let gbk = GBK.encode("中文日志").0.into_owned();
+        let (text, encoding) = decode_text(&gbk).unwrap();
+        assert_eq!(text, "中文日志");
+        assert_eq!(encode_text(&text, encoding), gbk);
+        assert_eq!(decode_entry_name(&gbk), "中文日志");
+        assert_eq!(entry_path("a.war", "WEB-INF/x.jar"), "a.war!/WEB-INF/x.jar");
+    }
+}
@@ -0,0 +1,176 @@
+// 查找文本中的 Base64/十六进制编码片段并解码，用于对编码后的内容重新匹配规则
+pub const MIN_BLOB_LEN: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct Blob {
+    pub start: usize,
+    pub end: usize,
+    pub kind: &'static str,  // base64 或 hex
+    pub text: String,        // 解码后的文本
+}
+
+fn is_blob_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'-' | b'_')
+}
+
+fn base64_value(b: u8) -> Option<u32> {
+    match b {
+        b'A'..=b'Z' => Some((b - b'A') as u32),
+        b'a'..=b'z' => Some((b - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((b - b'0') as u32 + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+// 同时支持标准及 URL 安全字母表，忽略结尾的 =
+pub fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.trim_end_matches('=').as_bytes();
+    if bytes.len() % 4 == 1 {
+        return None;
+    }
+    let mut output = Vec::with_capacity(bytes.len() * 3 / 4);
+    for chunk in bytes.chunks(4) {
+        let mut buf = 0u32;
+        for (i, b) in chunk.iter().enumerate() {
+            buf |= base64_value(*b)? << (18 - 6 * i);
+        }
+        let decoded = [(buf >> 16) as u8, (buf >> 8) as u8, buf as u8];
+        output.extend_from_slice(&decoded[..chunk.len() - 1]);
+    }
+    Some(output)
+}
+
+pub fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+// 按原片段的字母表及填充方式重新编码，用于脱敏后写回
+pub fn encode_base64(bytes: &[u8], original: &str) -> String {
+    let alphabet: &[u8; 64] = if original.contains(['-', '_']) {
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+    } else {
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+    };
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let buf = chunk.iter().enumerate().fold(0u32, |buf, (i, b)| buf | (*b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            output.push(alphabet[(buf >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    if original.ends_with('=') {
+        while !output.len().is_multiple_of(4) {
+            output.push('=');
+        }
+    }
+    output
+}
+
+pub fn encode_hex(bytes: &[u8], original: &str) -> String {
+    let upper = original.bytes().any(|b| b.is_ascii_uppercase());
+    bytes
+        .iter()
+        .map(|b| if upper { format!("{:02X}", b) } else { format!("{:02x}", b) })
+        .collect()
+}
+
+// 解码结果需要是可读文本，过滤掉恰好由合法字符组成的普通单词或路径
+fn as_text(bytes: Vec<u8>) -> Option<String> {
+    let text = String::from_utf8(bytes).ok()?;
+    let total = text.chars().count();
+    if total < 4 || text.chars().any(|c| c.is_control() && !c.is_whitespace()) {
+        return None;
+    }
+    let readable = text.chars().filter(|c| c.is_alphanumeric()).count();
+    if readable * 2 < total {
+        return None;
+    }
+    Some(text)
+}
+
+pub fn find_blobs(text: &str) -> Vec<Blob> {
+    let bytes = text.as_bytes();
+    let mut blobs = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !is_blob_char(bytes[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_blob_char(bytes[i]) {
+            i += 1;
+        }
+        let run_end = i;
+        // 最多两个 = 作为 Base64 填充
+        while i < bytes.len() && i - run_end < 2 && bytes[i] == b'=' {
+            i += 1;
+        }
+        if run_end - start < MIN_BLOB_LEN {
+            continue;
+        }
+        let candidate = &text[start..i];
+        let is_hex = text[start..run_end].bytes().all(|b| b.is_ascii_hexdigit()) && i == run_end;
+        let decoded = if is_hex {
+            decode_hex(candidate).and_then(as_text).map(|t| (t, "hex"))
+        } else {
+            None
+        };
+        let decoded = decoded.or_else(|| decode_base64(candidate).and_then(as_text).map(|t| (t, "base64")));
+        if let Some((decoded_text, kind)) = decoded {
+            blobs.push(Blob {
+                start,
+                end: i,
+                kind,
+                text: decoded_text,
+            });
+        }
+    }
+    blobs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip_keeps_alphabet_and_padding() {
+        let bytes = b"\xfb\xff\xbfpassword";
+        let standard = encode_base64(bytes, "a+b=");
+        assert_eq!(standard, "+/+/cGFzc3dvcmQ=");
+        assert_eq!(encode_base64(bytes, "a-b"), "-_-_cGFzc3dvcmQ");
+        assert_eq!(decode_base64(&standard).unwrap(), bytes);
+        assert_eq!(decode_base64("-_-_cGFzc3dvcmQ").unwrap(), bytes);
+        assert_eq!(decode_base64("abcde"), None);
+        assert_eq!(decode_base64("ab!d"), None);
+    }
+
+    #[test]
+    fn hex_round_trip_keeps_case() {
+        assert_eq!(encode_hex(b"\xab\x01", "ff"), "ab01");
+        assert_eq!(encode_hex(b"\xab\x01", "FF"), "AB01");
+        assert_eq!(decode_hex("AB01"), Some(vec![0xab, 0x01]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+
+    #[test]
+    fn find_blobs_decodes_readable_text() {
+        let text = "a=cGFzc3dvcmQ9c2VjcmV0MQ== b=70617373776f72643d736563726574 c=/usr/local/share/java/lib";
+        let blobs = find_blobs(text);
+        assert_eq!(blobs.len(), 2);
+        assert_eq!((blobs[0].kind, blobs[0].text.as_str()), ("base64", "password=secret1"));
+        assert_eq!(&text[blobs[0].start..blobs[0].end], "cGFzc3dvcmQ9c2VjcmV0MQ==");
+        assert_eq!((blobs[1].kind, blobs[1].text.as_str()), ("hex", "password=secret"));
+        // 过短的片段不解码
+        assert!(find_blobs("cGFzcw==").is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+// 内置规则库，界面中的默认规则及规则自测的示例
+use crate::rule::Rule;
+
+// 内置规则：(正则, 应当命中的示例, 不应命中的示例)，示例用于规则自测
+pub type BuiltinRule = (&'static str, &'static [&'static str], &'static [&'static str]);
+
+pub const LOG_RULES: &[(&str, &[BuiltinRule])] = &[
+    ("日志规则库", &[
+        (r"(?<!\d)(1\d{10})(?!\d)", &["mobile=13800138000"], &["order=138001380001"]),  // 手机号
+        (r"[a-zA-Z0-9\*]+\@[a-zA-Z0-9]+\.[a-zA-Z]+", &["mail: zhangsan@example.com"], &["mail: zhang***@example.com"]),  // 邮箱
+        (r"(?<!\d)(\d{17}[Xx]|\d{18})(?!\d)", &["idcard=11010519491231002X"], &["idcard=110105194912310021", "idcard=11010519491331002X"]),  // 身份证号
+        ("((P|p)ass(P|p)ort((N|n)o(s|S)?)?(\\s)?\"?(\\s)?\\:(\\s)?(\\[)?\"?[a-zA-Z0-9]+\"?[,;]+)", &["\"passportNo\":\"E12345678\","], &["passport: E12345678"]),  // 护照号码
+    ]),
+    ("关键字匹配", &[
+        (r"(P|p)(A|a)(S|s)(S|s)(W|w)((O|o)(R|r))?(D|d)", &["password=123456", "PASSWD"], &["pass=123456"]),  // 更宽泛的密码匹配
+        (r"(A|a)(E|e)(S|s)_?(K|k)(E|e)(Y|y)", &["aes_key", "AESKEY"], &["aes-key"]),  // AES key 匹配
+        (r"(A|a)(P|p)(P|p)_?(K|k)(E|e)(Y|y)", &["appKey", "app_key"], &["app-key"]),
+        (r"(S|s)_?(K|k)(E|e)(Y|y)", &["sKey", "s_key"], &["sky"]),
+        (r"(A|a)ccess_?(T|t)oken", &["accessToken", "access_token"], &["Access-Token"]),
+        ("(T|t)oken\\\"\\:\t", &["\"token\":\t\"abc\""], &["\"token\": \"abc\""]),
+        ("(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)\\\"\\:\t", &["\"secret\":\t\"abc\""], &["\"secret\": \"abc\""]),
+        ("(C|c)ertificate", &["Certificate"], &["cert"]),
+        ("(I|i)(D|d)_?(C|c)ard", &["idCard=1", "ID_Card"], &["id-card"]),
+    ]),
+];
+
+pub const PACKAGE_RULES: &[(&str, &[BuiltinRule])] = &[
+    ("发布包规则匹配", &[
+        (r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\s?[\"\']?(=|:)+\s?[\"\']?[a-zA-Z0-9\@\.]+[\"\']?"#, &["password=Passw0rd", "secret: \"abc123\""], &["password is not set"]),  // 因地制宜的密钥匹配,在class/java中使用内置的替代正则，强制搜索引号包裹的
+        (r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)[\"\']?\s?value(=|:)+[\"\']?[a-zA-Z0-9\@\.]+[\"\']?"#, &["name=\"password\" value=\"Passw0rd\""], &["password value"]),
+        (r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)[\"\']?\>+\s?[a-zA-Z0-9\@\.]+\<[\"\']?"#, &["<password>Passw0rd</password>"], &["<password></password>"]),
+        (r#"(S|s)(E|e)(T|t)([a-zA-Z0-9]+)?((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\(\s?[\"\']+[a-zA-Z0-9\@\.]+[\"\']+\s?\)"#, &["setPassword(\"Passw0rd\")"], &["setPassword(password)"]),
+        (r#"[\"\']+[a-zA-Z0-9\@\.]+[\"\']+\s?\,\s?((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)+"#, &["\"Passw0rd\", password"], &["Passw0rd, password"]),
+        (r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)+\s?\,\s?[\"\']+[a-zA-Z0-9\@\.]+[\"\']+"#, &["password, \"Passw0rd\""], &["password, Passw0rd"]),
+    ]),
+    ("发布包关键字匹配", &[
+        (r"(J|j)(W|w)(T|t)\.(A|a)(L|l)(G|g)(O|o)(R|r)(I|i)(T|t)(H|h)(M|m)", &["jwt.algorithm=HS256"], &["jwt_algorithm"]),  // JWT算法
+        (r"(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)", &["SECRET", "client.secret"], &["secre"]),  // SECRET 匹配
+        (r"(P|p)(A|a)(S|s)(S|s)(W|w)((O|o)(R|r))?(D|d)", &["password=123456", "PASSWD"], &["pass=123456"]),  // 更宽泛的密码匹配
+        (r"(A|a)(E|e)(S|s)_?(K|k)(E|e)(Y|y)", &["aes_key", "AESKEY"], &["aes-key"]),  // AES key 匹配
+    ]),
+];
+
+// 内置规则带上规则库中的示例
+pub fn builtin_rule(pattern: &str) -> Rule {
+    let mut rule = Rule::builtin(pattern);
+    let builtin = LOG_RULES.iter().chain(PACKAGE_RULES)
+        .flat_map(|(_, rules)| rules.iter())
+        .find(|(p, _, _)| *p == pattern);
+    if let Some((_, should_match, should_not_match)) = builtin {
+        rule.should_match = should_match.iter().map(|s| s.to_string()).collect();
+        rule.should_not_match = should_not_match.iter().map(|s| s.to_string()).collect();
+    }
+    rule
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Limits;
+    use crate::selftest::test_rule;
+
+    #[test]
+    fn builtin_rules_pass_their_examples() {
+        for (group, rules) in LOG_RULES.iter().chain(PACKAGE_RULES) {
+            for (i, (pattern, should_match, _)) in rules.iter().enumerate() {
+                let rule = builtin_rule(pattern);
+                assert!(!should_match.is_empty(), "{} 第{}条没有示例", group, i + 1);
+                let failures = test_rule(&format!("{} 第{}条", group, i + 1), &rule, &Limits::default());
+                assert!(failures.is_empty(), "{:?}", failures);
+            }
+        }
+    }
+
+    #[test]
+    fn builtin_rule_keeps_code_variants() {
+        let rule = builtin_rule(PACKAGE_RULES[0].1[0].0);
+        assert_eq!(rule.variants.len(), 1);
+        assert_eq!(rule.should_match, vec!["password=Passw0rd", "secret: \"abc123\""]);
+        assert!(builtin_rule("custom").should_match.is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+// 脱敏合规检查：判断日志中的个人信息是否按策略正确脱敏，并按文件统计
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rule::Rule;
+use crate::MatchResult;
+
+// 内置日志规则
+pub const PHONE_RULE: &str = r"(?<!\d)(1\d{10})(?!\d)";
+pub const EMAIL_RULE: &str = r"[a-zA-Z0-9\*]+\@[a-zA-Z0-9]+\.[a-zA-Z]+";
+pub const IDCARD_RULE: &str = r"(?<!\d)(\d{17}[Xx]|\d{18})(?!\d)";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaskPolicy {
+    pub label: String,           // 统计时展示的名称，如 手机号
+    pub masked_pattern: String,  // 匹配已脱敏形态的正则，必须要求至少包含一个 *
+    pub min_masked: usize,       // 至少遮盖多少个字符才算正确脱敏
+}
+
+impl MaskPolicy {
+    // 内置日志规则的默认策略
+    pub fn builtin(pattern: &str) -> Option<MaskPolicy> {
+        let (label, masked_pattern, min_masked) = match pattern {
+            PHONE_RULE => ("手机号", r"(?<![\d*])(?=1\d*\*)1[\d*]{10}(?![\d*])", 4),
+            EMAIL_RULE => ("邮箱", r"[a-zA-Z0-9]*\*[a-zA-Z0-9\*]*\@[a-zA-Z0-9]+\.[a-zA-Z]+", 3),
+            IDCARD_RULE => ("身份证号", r"(?<![\d*])(?=\d*\*)[\d*]{17}[\dXx*](?![\d*Xx])", 8),
+            _ => return None,
+        };
+        Some(MaskPolicy {
+            label: label.to_string(),
+            masked_pattern: masked_pattern.to_string(),
+            min_masked,
+        })
+    }
+
+    pub fn classify(&self, matched_text: &str) -> MaskClass {
+        let masked = matched_text.chars().filter(|c| *c == '*').count();
+        if masked == 0 {
+            MaskClass::Unmasked
+        } else if masked < self.min_masked {
+            MaskClass::Partial
+        } else {
+            MaskClass::Masked
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskClass {
+    Unmasked,  // 未脱敏
+    Partial,   // 部分脱敏，遮盖不足
+    Masked,    // 已正确脱敏
+}
+
+pub fn rule_policy(rule: &Rule) -> Option<MaskPolicy> {
+    rule.policy.clone().or_else(|| MaskPolicy::builtin(&rule.pattern))
+}
+
+// 合规检查时需要同时搜索规则本身和已脱敏形态，已脱敏形态沿用规则的作用范围及匹配限制
+pub fn compliance_rules(rules: &[Rule]) -> Vec<Rule> {
+    let mut compliance = Vec::new();
+    for rule in rules {
+        if let Some(policy) = rule_policy(rule) {
+            compliance.push(rule.clone());
+            compliance.push(Rule {
+                include: rule.include.clone(),
+                exclude: rule.exclude.clone(),
+                match_limit: rule.match_limit,
+                depth_limit: rule.depth_limit,
+                ..Rule::new(&policy.masked_pattern)
+            });
+        }
+    }
+    compliance
+}
+
+#[derive(Debug, Clone)]
+pub struct ComplianceSummary {
+    pub file_name: String,
+    pub label: String,
+    pub unmasked: Vec<String>,  // 未脱敏的行号
+    pub partial: Vec<String>,   // 部分脱敏的行号
+    pub masked: usize,
+}
+
+impl ComplianceSummary {
+    pub fn total(&self) -> usize {
+        self.unmasked.len() + self.partial.len() + self.masked
+    }
+
+    // 向下取整，只要存在未脱敏的值就不会显示 100%
+    pub fn masked_percent(&self) -> usize {
+        self.masked * 100 / self.total().max(1)
+    }
+}
+
+impl fmt::Display for ComplianceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}% 的{}已脱敏（未脱敏 {}，部分脱敏 {}，已脱敏 {}）",
+            self.file_name,
+            self.masked_percent(),
+            self.label,
+            self.unmasked.len(),
+            self.partial.len(),
+            self.masked
+        )
+    }
+}
+
+// 将按 compliance_rules 扫描得到的结果按文件和规则汇总，同一行中的每个值分别计数
+pub fn summarize(results: &[MatchResult], rules: &[Rule]) -> Vec<ComplianceSummary> {
+    let policies: Vec<(&Rule, MaskPolicy)> = rules
+        .iter()
+        .filter_map(|rule| rule_policy(rule).map(|policy| (rule, policy)))
+        .collect();
+
+    let mut summaries: Vec<ComplianceSummary> = Vec::new();
+    for result in results.iter().filter(|result| !result.is_issue()) {
+        let policy = match policies
+            .iter()
+            .find(|(rule, policy)| rule.pattern == result.rule || policy.masked_pattern == result.rule)
+        {
+            Some((_, policy)) => policy,
+            None => continue,
+        };
+        let index = match summaries
+            .iter()
+            .position(|s| s.file_name == result.file_name && s.label == policy.label)
+        {
+            Some(index) => index,
+            None => {
+                summaries.push(ComplianceSummary {
+                    file_name: result.file_name.clone(),
+                    label: policy.label.clone(),
+                    unmasked: Vec::new(),
+                    partial: Vec::new(),
+                    masked: 0,
+                });
+                summaries.len() - 1
+            }
+        };
+        let summary = &mut summaries[index];
+        match policy.classify(&result.matched_text) {
+            MaskClass::Unmasked => summary.unmasked.push(result.line_number.clone()),
+            MaskClass::Partial => summary.partial.push(result.line_number.clone()),
+            MaskClass::Masked => summary.masked += 1,
+        }
+    }
+    summaries.sort_by(|a, b| a.file_name.cmp(&b.file_name).then_with(|| a.label.cmp(&b.label)));
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule::RuleSet;
+    use crate::search_in_file_contents;
+
+    fn scan(rules: &[Rule], contents: &str, file_name: &str) -> Vec<MatchResult> {
+        search_in_file_contents(&RuleSet::new(compliance_rules(rules)), contents, file_name)
+    }
+
+    #[test]
+    fn classify_by_masked_characters() {
+        let policy = MaskPolicy::builtin(PHONE_RULE).unwrap();
+        assert_eq!(policy.classify("13800138000"), MaskClass::Unmasked);
+        assert_eq!(policy.classify("1380013800*"), MaskClass::Partial);
+        assert_eq!(policy.classify("138****8000"), MaskClass::Masked);
+        assert!(MaskPolicy::builtin("password").is_none());
+    }
+
+    #[test]
+    fn compliance_rules_keep_scope_and_limits() {
+        let rule = Rule {
+            include: vec!["*.log".to_string()],
+            match_limit: Some(100),
+            ..Rule::new(PHONE_RULE)
+        };
+        let rules = compliance_rules(&[rule.clone(), Rule::new("password")]);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0], rule);
+        assert_eq!(rules[1].pattern, MaskPolicy::builtin(PHONE_RULE).unwrap().masked_pattern);
+        assert_eq!((rules[1].include.clone(), rules[1].match_limit), (rule.include, Some(100)));
+    }
+
+    #[test]
+    fn summarize_counts_every_value_on_a_line() {
+        let rules = vec![Rule::new(PHONE_RULE), Rule::new(EMAIL_RULE)];
+        let contents = "call 13800138000 or 13900139000\n\
+                        masked 138****8000 and 139****9000\n\
+                        partial 1380013800* mail bob@example.com b**@example.com\n";
+        let summaries = summarize(&scan(&rules, contents, "app.log"), &rules);
+        assert_eq!(summaries.len(), 2);
+
+        let phone = &summaries[0];
+        assert_eq!(phone.label, "手机号");
+        assert_eq!(phone.unmasked, vec!["1", "1"]);
+        assert_eq!(phone.partial, vec!["3"]);
+        assert_eq!(phone.masked, 2);
+        assert_eq!(phone.total(), 5);
+        assert_eq!(phone.masked_percent(), 40);
+        assert_eq!(phone.to_string(), "app.log: 40% 的手机号已脱敏（未脱敏 2，部分脱敏 1，已脱敏 2）");
+
+        let email = &summaries[1];
+        assert_eq!(email.label, "邮箱");
+        assert_eq!((email.unmasked.len(), email.partial.len(), email.masked), (1, 1, 0));
+    }
+
+    #[test]
+    fn summarize_groups_by_file() {
+        let rules = vec![Rule::new(PHONE_RULE)];
+        let mut results = scan(&rules, "138****8000", "b.log");
+        results.extend(scan(&rules, "13800138000", "a.log"));
+        let summaries = summarize(&results, &rules);
+        let files: Vec<&str> = summaries.iter().map(|s| s.file_name.as_str()).collect();
+        assert_eq!(files, vec!["a.log", "b.log"]);
+        assert_eq!(summaries[0].masked_percent(), 0);
+        assert_eq!(summaries[1].masked_percent(), 100);
+    }
+}
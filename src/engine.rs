@@ -0,0 +1,596 @@
+// 正则匹配引擎：默认使用 PCRE2（尽量 JIT 编译），pcre2 crate 不支持设置匹配限制，这里直接调用 pcre2-sys，
+// 为每条规则设置匹配次数、回溯深度限制及可选的单行超时，避免灾难性回溯卡住扫描。
+// 启用 pure-rust 特性后，不需要 PCRE 专有语法的规则改用 regex 编译，关闭 pcre2 特性则不再依赖 C 库，
+// 此时使用环视、反向引用的规则由 fancy-regex 编译
+use std::borrow::Cow;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+#[cfg(not(any(feature = "pcre2", feature = "pure-rust")))]
+compile_error!("至少需要启用 pcre2 或 pure-rust 特性之一");
+
+// 默认匹配次数限制，PCRE2 自身默认为一千万
+pub const DEFAULT_MATCH_LIMIT: u32 = 1_000_000;
+// 默认回溯深度限制
+pub const DEFAULT_DEPTH_LIMIT: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub match_limit: u32,
+    pub depth_limit: Option<u32>,       // 回溯深度限制，不设置时使用 DEFAULT_DEPTH_LIMIT 并允许 JIT；设置后 PCRE2 解释执行
+    pub line_budget: Option<Duration>,  // 单行匹配时间上限，开启后正则不能使用 JIT，匹配会变慢
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            match_limit: DEFAULT_MATCH_LIMIT,
+            depth_limit: None,
+            line_budget: None,
+        }
+    }
+}
+
+// 规则实际使用的正则引擎
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Engine {
+    Pcre2Jit,    // PCRE2 JIT 编译，最快，不支持单行超时，回溯深度只受 JIT 栈大小限制
+    Pcre2,       // PCRE2 解释执行
+    Regex,       // regex crate，线性时间匹配，不会灾难性回溯
+    FancyRegex,  // fancy-regex，支持环视与反向引用，只有回溯次数限制
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Engine::Pcre2Jit => write!(f, "PCRE2 JIT"),
+            Engine::Pcre2 => write!(f, "PCRE2"),
+            Engine::Regex => write!(f, "regex"),
+            Engine::FancyRegex => write!(f, "fancy-regex"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    pub offset: Option<usize>,  // 出错位置在正则中的字节偏移，纯 Rust 引擎不提供
+    pub message: String,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "正则编译失败，偏移 {}：{}", offset, self.message),
+            None => write!(f, "正则编译失败：{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+// 匹配中止的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchError {
+    MatchLimit,
+    DepthLimit,
+    Timeout,
+    Other(String),
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatchError::MatchLimit => write!(f, "超出匹配次数限制"),
+            MatchError::DepthLimit => write!(f, "超出回溯深度限制"),
+            MatchError::Timeout => write!(f, "超出单行匹配时间限制"),
+            MatchError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for MatchError {}
+
+enum Compiled {
+    #[cfg(feature = "pcre2")]
+    Pcre2(pcre::Pcre2Matcher),
+    #[cfg(feature = "pure-rust")]
+    Regex(regex::bytes::Regex),
+    #[cfg(not(feature = "pcre2"))]
+    FancyRegex(fancy_regex::Regex),
+}
+
+pub struct Matcher {
+    compiled: Compiled,
+    engine: Engine,
+    line_budget: Option<Duration>,
+}
+
+impl Matcher {
+    // 按 regex、PCRE2、fancy-regex 的顺序尝试编译，未启用的引擎跳过
+    pub fn new(pattern: &str, limits: &Limits) -> Result<Matcher, CompileError> {
+        #[cfg(feature = "pure-rust")]
+        if let Ok(regex) = regex::bytes::RegexBuilder::new(&pcre_compat(pattern)).unicode(false).build() {
+            return Ok(Matcher {
+                compiled: Compiled::Regex(regex),
+                engine: Engine::Regex,
+                line_budget: limits.line_budget,
+            });
+        }
+        #[cfg(feature = "pcre2")]
+        {
+            let matcher = pcre::Pcre2Matcher::new(pattern, limits)?;
+            let engine = if matcher.is_jit() { Engine::Pcre2Jit } else { Engine::Pcre2 };
+            Ok(Matcher {
+                compiled: Compiled::Pcre2(matcher),
+                engine,
+                line_budget: limits.line_budget,
+            })
+        }
+        #[cfg(not(feature = "pcre2"))]
+        {
+            let regex = fancy_regex::RegexBuilder::new(&ascii_classes(&pcre_compat(pattern)))
+                .backtrack_limit(limits.match_limit as usize)
+                .build()
+                .map_err(|e| CompileError {
+                    offset: None,
+                    message: e.to_string(),
+                })?;
+            Ok(Matcher {
+                compiled: Compiled::FancyRegex(regex),
+                engine: Engine::FancyRegex,
+                line_budget: limits.line_budget,
+            })
+        }
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    // 从现在开始计算的单行匹配截止时间，未设置单行超时时为空；同一行的多次查找应共用一个截止时间
+    pub fn deadline(&self) -> Option<Instant> {
+        self.line_budget.map(|budget| Instant::now() + budget)
+    }
+
+    // 从 start 开始查找第一个匹配，返回匹配的字节范围
+    pub fn find_at(&self, subject: &[u8], start: usize) -> Result<Option<(usize, usize)>, MatchError> {
+        self.find_at_until(subject, start, self.deadline())
+    }
+
+    // 同 find_at，超过 deadline 时中止匹配；只有 PCRE2 解释执行支持超时
+    #[cfg_attr(not(feature = "pcre2"), allow(unused_variables))]
+    pub fn find_at_until(&self, subject: &[u8], start: usize, deadline: Option<Instant>) -> Result<Option<(usize, usize)>, MatchError> {
+        match &self.compiled {
+            #[cfg(feature = "pcre2")]
+            Compiled::Pcre2(matcher) => matcher.find_at(subject, start, deadline),
+            #[cfg(feature = "pure-rust")]
+            Compiled::Regex(regex) => Ok(regex.find_at(subject, start).map(|m| (m.start(), m.end()))),
+            #[cfg(not(feature = "pcre2"))]
+            Compiled::FancyRegex(regex) => {
+                // fancy-regex 只能匹配字符串，起点需要落在字符边界上
+                let text = std::str::from_utf8(subject).map_err(|_| MatchError::Other("fancy-regex 只能匹配 UTF-8 文本".to_string()))?;
+                let mut start = start;
+                while start < text.len() && !text.is_char_boundary(start) {
+                    start += 1;
+                }
+                match regex.find_from_pos(text, start) {
+                    Ok(found) => Ok(found.map(|m| (m.start(), m.end()))),
+                    Err(fancy_regex::Error::RuntimeError(fancy_regex::RuntimeError::BacktrackLimitExceeded)) => Err(MatchError::MatchLimit),
+                    Err(e) => Err(MatchError::Other(e.to_string())),
+                }
+            }
+        }
+    }
+
+    pub fn find(&self, subject: &[u8]) -> Result<Option<(usize, usize)>, MatchError> {
+        self.find_at(subject, 0)
+    }
+
+    // 查找所有不重叠的匹配
+    pub fn find_all(&self, subject: &[u8]) -> Result<Vec<(usize, usize)>, MatchError> {
+        self.find_all_until(subject, self.deadline())
+    }
+
+    // 同 find_all，所有查找共用同一个截止时间
+    pub fn find_all_until(&self, subject: &[u8], deadline: Option<Instant>) -> Result<Vec<(usize, usize)>, MatchError> {
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start <= subject.len() {
+            match self.find_at_until(subject, start, deadline)? {
+                Some((s, e)) => {
+                    matches.push((s, e));
+                    // 空匹配时向后移动一个字节，避免死循环
+                    start = if e > s { e } else { e + 1 };
+                }
+                None => break,
+            }
+        }
+        Ok(matches)
+    }
+}
+
+// PCRE 中 \< 与 \> 只是转义的尖括号，regex 中却是单词边界，交给纯 Rust 引擎前先去掉转义
+#[cfg_attr(not(feature = "pure-rust"), allow(dead_code))]
+fn pcre_compat(pattern: &str) -> Cow<'_, str> {
+    if !pattern.contains("\\<") && !pattern.contains("\\>") {
+        return Cow::Borrowed(pattern);
+    }
+    let mut output = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(next @ ('<' | '>')) => output.push(next),
+            Some(next) => {
+                output.push('\\');
+                output.push(next);
+            }
+            None => output.push('\\'),
+        }
+    }
+    Cow::Owned(output)
+}
+
+// fancy-regex 没有关闭 Unicode 的选项，\d、\w、\s 会匹配全角数字等多字节字符，与 PCRE2、regex（关闭 Unicode）的结果不一致，
+// 这里改写成等价的 ASCII 字符类
+#[cfg(not(feature = "pcre2"))]
+fn ascii_classes(pattern: &str) -> Cow<'_, str> {
+    if !pattern.contains('\\') {
+        return Cow::Borrowed(pattern);
+    }
+    let mut output = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    let mut in_class = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let Some(next) = chars.next() else {
+                    output.push('\\');
+                    break;
+                };
+                let replacement = match (next, in_class) {
+                    ('d', false) => "[0-9]",
+                    ('D', false) => "[^0-9]",
+                    ('w', false) => "[0-9A-Za-z_]",
+                    ('W', false) => "[^0-9A-Za-z_]",
+                    ('s', false) => "[\\t\\n\\x0B\\x0C\\r\\x20]",
+                    ('S', false) => "[^\\t\\n\\x0B\\x0C\\r\\x20]",
+                    ('d', true) => "0-9",
+                    ('w', true) => "0-9A-Za-z_",
+                    ('s', true) => "\\t\\n\\x0B\\x0C\\r\\x20",
+                    // 字符类中的取反只能用 POSIX 字符类表示，regex 中 POSIX 字符类只含 ASCII
+                    ('D', true) => "[:^digit:]",
+                    ('W', true) => "[:^word:]",
+                    ('S', true) => "[:^space:]",
+                    _ => {
+                        output.push('\\');
+                        output.push(next);
+                        continue;
+                    }
+                };
+                output.push_str(replacement);
+            }
+            '[' if !in_class => {
+                in_class = true;
+                output.push(c);
+                // 紧跟在 [ 或 [^ 后的 ] 是字面量
+                if chars.peek() == Some(&'^') {
+                    output.push(chars.next().unwrap_or_default());
+                }
+                if chars.peek() == Some(&']') {
+                    output.push(chars.next().unwrap_or_default());
+                }
+            }
+            // 字符类中的 POSIX 字符类，如 [[:alpha:]]
+            '[' if chars.peek() == Some(&':') => {
+                output.push(c);
+                for c in chars.by_ref() {
+                    output.push(c);
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            ']' if in_class => {
+                in_class = false;
+                output.push(c);
+            }
+            c => output.push(c),
+        }
+    }
+    Cow::Owned(output)
+}
+
+#[cfg(feature = "pcre2")]
+mod pcre {
+    use std::os::raw::{c_int, c_void};
+    use std::ptr;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    use pcre2_sys::{
+        pcre2_code_8, pcre2_code_free_8, pcre2_compile_8, pcre2_get_error_message_8, pcre2_get_ovector_pointer_8,
+        pcre2_jit_compile_8, pcre2_jit_stack_8, pcre2_jit_stack_assign_8, pcre2_jit_stack_create_8,
+        pcre2_jit_stack_free_8, pcre2_match_8, pcre2_match_context_8, pcre2_match_context_create_8,
+        pcre2_match_context_free_8, pcre2_match_data_8, pcre2_match_data_create_from_pattern_8,
+        pcre2_match_data_free_8, pcre2_set_depth_limit_8, pcre2_set_match_limit_8, PCRE2_AUTO_CALLOUT,
+        PCRE2_ERROR_CALLOUT, PCRE2_ERROR_DEPTHLIMIT, PCRE2_ERROR_JIT_STACKLIMIT, PCRE2_ERROR_MATCHLIMIT,
+        PCRE2_ERROR_NOMATCH, PCRE2_JIT_COMPLETE,
+    };
+
+    use super::{CompileError, Limits, MatchError};
+
+    // pcre2-sys 0.2 没有导出设置 callout 的函数，按 pcre2.h 声明；该函数自 PCRE2 10.00 起提供，
+    // pcre2-sys 0.2.10 内置的是 PCRE2 10.46，链接系统库时同样要求 10.x
+    extern "C" {
+        fn pcre2_set_callout_8(
+            mcontext: *mut pcre2_match_context_8,
+            callout: Option<unsafe extern "C" fn(*mut c_void, *mut c_void) -> c_int>,
+            data: *mut c_void,
+        ) -> c_int;
+    }
+
+    // 每隔多少次 callout 检查一次是否超时
+    const CALLOUT_CHECK_INTERVAL: u32 = 1024;
+    // JIT 栈的初始与最大大小，默认的 32K 对较长的行不够用
+    const JIT_STACK_START: usize = 32 * 1024;
+    const JIT_STACK_MAX: usize = 4 * 1024 * 1024;
+
+    fn error_message(code: c_int) -> String {
+        let mut buf = [0u8; 240];
+        let len = unsafe { pcre2_get_error_message_8(code, buf.as_mut_ptr(), buf.len()) };
+        if len < 0 {
+            return format!("PCRE2 错误 {}", code);
+        }
+        String::from_utf8_lossy(&buf[..len as usize]).to_string()
+    }
+
+    // callout 回调的状态，只在一次匹配期间有效
+    struct Deadline {
+        deadline: Instant,
+        calls: u32,
+        expired: bool,
+    }
+
+    unsafe extern "C" fn check_deadline(_block: *mut c_void, data: *mut c_void) -> c_int {
+        let deadline = unsafe { &mut *(data as *mut Deadline) };
+        deadline.calls += 1;
+        if deadline.calls % CALLOUT_CHECK_INTERVAL == 0 && Instant::now() >= deadline.deadline {
+            deadline.expired = true;
+            // 返回负数使 pcre2_match 立即中止
+            return PCRE2_ERROR_CALLOUT;
+        }
+        0
+    }
+
+    // 一次匹配使用的匹配数据、匹配上下文及 JIT 栈，不能同时在多个线程中使用
+    struct MatchState {
+        match_data: *mut pcre2_match_data_8,
+        context: *mut pcre2_match_context_8,
+        jit_stack: *mut pcre2_jit_stack_8,  // 未使用 JIT 或创建失败时为空，此时使用默认的 JIT 栈
+    }
+
+    impl Drop for MatchState {
+        fn drop(&mut self) {
+            unsafe {
+                pcre2_match_context_free_8(self.context);
+                if !self.jit_stack.is_null() {
+                    pcre2_jit_stack_free_8(self.jit_stack);
+                }
+                pcre2_match_data_free_8(self.match_data);
+            }
+        }
+    }
+
+    pub struct Pcre2Matcher {
+        code: *mut pcre2_code_8,
+        jit: bool,
+        limits: Limits,
+        // 用完的匹配状态放回池中复用，各线程同时匹配时各取一份
+        states: Mutex<Vec<MatchState>>,
+    }
+
+    // 编译后的正则（包括 JIT 代码）匹配时只读，PCRE2 允许多个线程共用；
+    // 每次匹配可变的状态从池中独占取出，因此可以在线程间共享
+    unsafe impl Send for Pcre2Matcher {}
+    unsafe impl Sync for Pcre2Matcher {}
+    unsafe impl Send for MatchState {}
+
+    impl Pcre2Matcher {
+        pub fn new(pattern: &str, limits: &Limits) -> Result<Pcre2Matcher, CompileError> {
+            // 单行超时依赖自动 callout 定期检查时间
+            let options = if limits.line_budget.is_some() { PCRE2_AUTO_CALLOUT } else { 0 };
+            let (mut error_code, mut error_offset) = (0, 0);
+            let code = unsafe {
+                pcre2_compile_8(pattern.as_ptr(), pattern.len(), options, &mut error_code, &mut error_offset, ptr::null_mut())
+            };
+            if code.is_null() {
+                return Err(CompileError {
+                    offset: Some(error_offset),
+                    message: error_message(error_code),
+                });
+            }
+            // JIT 不支持单行超时及回溯深度限制（只受 JIT 栈大小约束），开启单行超时或配置了回溯深度时保持解释执行，
+            // 以便这些限制生效；平台不支持 JIT 时同样退回解释执行
+            let jit = limits.line_budget.is_none()
+                && limits.depth_limit.is_none()
+                && unsafe { pcre2_jit_compile_8(code, PCRE2_JIT_COMPLETE) } == 0;
+            Ok(Pcre2Matcher {
+                code,
+                jit,
+                limits: *limits,
+                states: Mutex::new(Vec::new()),
+            })
+        }
+
+        pub fn is_jit(&self) -> bool {
+            self.jit
+        }
+
+        fn new_state(&self) -> MatchState {
+            let match_data = unsafe { pcre2_match_data_create_from_pattern_8(self.code, ptr::null_mut()) };
+            let context = unsafe { pcre2_match_context_create_8(ptr::null_mut()) };
+            assert!(!match_data.is_null() && !context.is_null(), "PCRE2 内存分配失败");
+            unsafe {
+                pcre2_set_match_limit_8(context, self.limits.match_limit);
+                pcre2_set_depth_limit_8(context, self.limits.depth_limit.unwrap_or(super::DEFAULT_DEPTH_LIMIT));
+            }
+            let mut jit_stack = ptr::null_mut();
+            if self.jit {
+                jit_stack = unsafe { pcre2_jit_stack_create_8(JIT_STACK_START, JIT_STACK_MAX, ptr::null_mut()) };
+                if !jit_stack.is_null() {
+                    unsafe { pcre2_jit_stack_assign_8(context, None, jit_stack as *mut c_void) };
+                }
+            }
+            MatchState {
+                match_data,
+                context,
+                jit_stack,
+            }
+        }
+
+        pub fn find_at(&self, subject: &[u8], start: usize, deadline: Option<Instant>) -> Result<Option<(usize, usize)>, MatchError> {
+            let popped = self.states.lock().unwrap_or_else(|e| e.into_inner()).pop();
+            let state = popped.unwrap_or_else(|| self.new_state());
+            let result = self.find_with(&state, subject, start, deadline);
+            self.states.lock().unwrap_or_else(|e| e.into_inner()).push(state);
+            result
+        }
+
+        // deadline 由调用方按行计算，同一行的多次匹配共用，只在编译时开启了 callout 的正则上生效
+        fn find_with(&self, state: &MatchState, subject: &[u8], start: usize, deadline: Option<Instant>) -> Result<Option<(usize, usize)>, MatchError> {
+            let mut deadline = deadline.filter(|_| self.limits.line_budget.is_some()).map(|deadline| Deadline {
+                deadline,
+                calls: 0,
+                expired: false,
+            });
+            if let Some(deadline) = deadline.as_mut() {
+                unsafe {
+                    pcre2_set_callout_8(state.context, Some(check_deadline), deadline as *mut Deadline as *mut c_void);
+                }
+            }
+            // 空切片的指针不一定可以解引用，换成一个有效的指针，长度仍传 0
+            static EMPTY: &[u8] = &[0];
+            let pointer = if subject.is_empty() { EMPTY.as_ptr() } else { subject.as_ptr() };
+            let rc = unsafe { pcre2_match_8(self.code, pointer, subject.len(), start, 0, state.match_data, state.context) };
+            if deadline.is_some() {
+                unsafe {
+                    pcre2_set_callout_8(state.context, None, ptr::null_mut());
+                }
+            }
+            match rc {
+                PCRE2_ERROR_NOMATCH => Ok(None),
+                PCRE2_ERROR_MATCHLIMIT => Err(MatchError::MatchLimit),
+                // JIT 栈耗尽相当于默认的回溯深度限制
+                PCRE2_ERROR_DEPTHLIMIT | PCRE2_ERROR_JIT_STACKLIMIT => Err(MatchError::DepthLimit),
+                _ if deadline.is_some_and(|d| d.expired) => Err(MatchError::Timeout),
+                rc if rc > 0 => {
+                    let ovector = unsafe { pcre2_get_ovector_pointer_8(state.match_data) };
+                    let (start, end) = unsafe { (*ovector, *ovector.add(1)) };
+                    Ok(Some((start, end)))
+                }
+                rc => Err(MatchError::Other(error_message(rc))),
+            }
+        }
+    }
+
+    impl Drop for Pcre2Matcher {
+        fn drop(&mut self) {
+            // 先释放匹配状态，再释放编译后的正则
+            self.states.get_mut().unwrap_or_else(|e| e.into_inner()).clear();
+            unsafe {
+                pcre2_code_free_8(self.code);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_all(pattern: &str, subject: &str) -> Vec<(usize, usize)> {
+        Matcher::new(pattern, &Limits::default()).unwrap().find_all(subject.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn pcre_compat_unescapes_angle_brackets() {
+        assert_eq!(pcre_compat(r"<a\>"), "<a>");
+        assert_eq!(pcre_compat(r"\<tag\>\d\\"), r"<tag>\d\\");
+        assert!(matches!(pcre_compat(r"a\d"), Cow::Borrowed(_)));
+        assert_eq!(find_all(r"\<key\>", "x<key>y"), vec![(1, 6)]);
+    }
+
+    #[test]
+    fn classes_match_ascii_only() {
+        // 全角数字不是 \d，各引擎结果一致
+        assert_eq!(find_all(r"\d+", "１２３ 456"), vec![(10, 13)]);
+        assert_eq!(find_all(r"(?<!\d)1\d{10}(?!\d)", "手机13800138000，１3800138000"), vec![(6, 17)]);
+        assert_eq!(find_all(r"\w+", "中文abc"), vec![(6, 9)]);
+    }
+
+    #[test]
+    fn find_all_returns_every_match() {
+        assert_eq!(find_all("a", "banana"), vec![(1, 2), (3, 4), (5, 6)]);
+        // 空匹配不会死循环
+        assert_eq!(find_all("x*", "ab"), vec![(0, 0), (1, 1), (2, 2)]);
+        assert!(find_all("z", "").is_empty());
+    }
+
+    #[test]
+    fn invalid_pattern_is_compile_error() {
+        assert!(Matcher::new("(", &Limits::default()).is_err());
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn plain_patterns_use_regex() {
+        let limits = Limits::default();
+        assert_eq!(Matcher::new(r"\d+", &limits).unwrap().engine(), Engine::Regex);
+        assert_ne!(Matcher::new(r"(?<!\d)\d+", &limits).unwrap().engine(), Engine::Regex);
+    }
+
+    #[cfg(all(feature = "pcre2", not(feature = "pure-rust")))]
+    #[test]
+    fn match_limit_stops_catastrophic_backtracking() {
+        let limits = Limits { match_limit: 1000, ..Limits::default() };
+        let matcher = Matcher::new(r"(a+)+$", &limits).unwrap();
+        let subject = format!("{}b", "a".repeat(30));
+        assert_eq!(matcher.find(subject.as_bytes()), Err(MatchError::MatchLimit));
+    }
+
+    #[cfg(all(feature = "pcre2", not(feature = "pure-rust")))]
+    #[test]
+    fn configured_depth_limit_disables_jit() {
+        let limits = Limits { depth_limit: Some(DEFAULT_DEPTH_LIMIT), ..Limits::default() };
+        assert_eq!(Matcher::new(r"(a+)+$", &limits).unwrap().engine(), Engine::Pcre2);
+    }
+
+    #[cfg(all(feature = "pcre2", not(feature = "pure-rust")))]
+    #[test]
+    fn line_deadline_is_shared_by_caller() {
+        let limits = Limits { match_limit: u32::MAX, line_budget: Some(Duration::from_secs(60)), ..Limits::default() };
+        let matcher = Matcher::new(r"(a+)+$", &limits).unwrap();
+        assert_eq!(matcher.engine(), Engine::Pcre2);
+        let subject = format!("{}b", "a".repeat(40));
+        // 调用方传入的截止时间已过，不会按单行超时重新计时
+        assert_eq!(matcher.find_all_until(subject.as_bytes(), Some(Instant::now())), Err(MatchError::Timeout));
+        assert_eq!(matcher.find_all_until(b"aaa", Some(Instant::now())), Ok(vec![(0, 3)]));
+    }
+
+    #[cfg(not(feature = "pcre2"))]
+    #[test]
+    fn ascii_classes_rewrites_shorthands() {
+        assert_eq!(ascii_classes(r"\d\w\s"), r"[0-9][0-9A-Za-z_][\t\n\x0B\x0C\r\x20]");
+        assert_eq!(ascii_classes(r"[\d\-x]"), r"[0-9\-x]");
+        assert_eq!(ascii_classes(r"[^\D]"), r"[^[:^digit:]]");
+        assert_eq!(ascii_classes(r"[]\d][[:alpha:]\w]"), r"[]0-9][[:alpha:]0-9A-Za-z_]");
+        assert_eq!(ascii_classes(r"\\d\."), r"\\d\.");
+        assert!(matches!(ascii_classes("abc"), Cow::Borrowed(_)));
+    }
+}
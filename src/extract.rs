@@ -0,0 +1,247 @@
+// 按 outer.war!/WEB-INF/lib/x.jar!/com/A.class 形式的路径取出嵌套在压缩包中的单个条目，或反编译其中的 class 供复查
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use sevenz_rust::BlockDecoder;
+use zip::read::ZipArchive;
+
+use crate::archive::{self, Format, TarReader, ENTRY_SEPARATOR};
+use crate::scanner::{decompile, expands, ArchiveLimits, CancelToken, LimitExceeded};
+
+type ExtractResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// 扫描结果中的路径相对于扫描的目录，扫描单个文件时只有文件名，绝对路径保持不变
+pub fn resolve_path(root: &Path, file_path: &str) -> PathBuf {
+    let base = if root.is_file() { root.parent().unwrap_or(Path::new("")) } else { root };
+    base.join(file_path)
+}
+
+// 一次提取的展开状态，与扫描使用同样的限制，超出时拒绝提取
+struct Expansion<'a> {
+    limits: &'a ArchiveLimits,
+    depth: usize,   // 已进入的压缩包层数
+    expanded: u64,  // 已解压出的字节数
+}
+
+impl Expansion<'_> {
+    fn refuse(&self, exceeded: LimitExceeded) -> Box<dyn Error + Send + Sync> {
+        format!("{}，拒绝提取", self.limits.describe(exceeded)).into()
+    }
+
+    fn enter_archive(&mut self) -> ExtractResult<()> {
+        if self.depth >= self.limits.max_depth {
+            return Err(self.refuse(LimitExceeded::Depth));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    // 检查解压出的内容，会继续展开的压缩包在展开时才计入解压总量
+    fn check(&mut self, bytes: &[u8], file_name: &str, compressed_size: u64) -> ExtractResult<()> {
+        let format = archive::detect_format(&bytes[..bytes.len().min(archive::SNIFF_LEN)], file_name);
+        self.limits.check(bytes.len() as u64, self.expanded, compressed_size).map_err(|exceeded| self.refuse(exceeded))?;
+        if !expands(format) {
+            self.expanded = self.expanded.saturating_add(bytes.len() as u64);
+        }
+        Ok(())
+    }
+
+    // 读出一个条目，最多读到超出限制为止
+    fn read(&mut self, reader: &mut dyn Read, file_name: &str, compressed_size: u64) -> ExtractResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        reader
+            .take(self.limits.read_limit(self.expanded, compressed_size).saturating_add(1))
+            .read_to_end(&mut bytes)?;
+        self.check(&bytes, file_name, compressed_size)?;
+        Ok(bytes)
+    }
+
+    // 跳过固实压缩中排在前面的条目，读出的内容同样计入解压总量
+    fn skip(&mut self, reader: &mut dyn Read) -> ExtractResult<()> {
+        let budget = self.limits.max_total_size.saturating_sub(self.expanded);
+        let drained = std::io::copy(&mut reader.take(budget.saturating_add(1)), &mut std::io::sink())?;
+        self.expanded = self.expanded.saturating_add(drained);
+        if drained > budget {
+            return Err(self.refuse(LimitExceeded::TotalSize));
+        }
+        Ok(())
+    }
+}
+
+// 取出条目内容，root 为产生该结果的扫描路径，路径的第一段为磁盘上的文件，不含 !/ 时直接读取该文件；
+// 压缩包按扫描时的展开限制解压，嵌套过深、超出大小或压缩比限制时拒绝提取
+pub fn extract_entry(root: &Path, path: &str, limits: &ArchiveLimits) -> ExtractResult<Vec<u8>> {
+    let mut segments = path.split(ENTRY_SEPARATOR);
+    let file_path = segments.next().unwrap_or_default();
+    let disk_path = resolve_path(root, file_path);
+    let file = File::open(&disk_path).map_err(|e| format!("读取 {} 失败: {}", disk_path.to_string_lossy(), e))?;
+    let mut container = file_path.to_string();
+    let mut expansion = Expansion {
+        limits,
+        depth: 0,
+        expanded: 0,
+    };
+    let mut bytes = match segments.next() {
+        // 磁盘上的压缩包通过目录定位条目，不整个读入内存
+        Some(entry_name) => {
+            let bytes = read_entry(BufReader::new(file), &container, entry_name, &mut expansion)?;
+            container = archive::entry_path(&container, entry_name);
+            bytes
+        }
+        None => {
+            let mut bytes = Vec::new();
+            BufReader::new(file).read_to_end(&mut bytes)?;
+            return Ok(bytes);
+        }
+    };
+    for entry_name in segments {
+        bytes = read_entry(Cursor::new(bytes), &container, entry_name, &mut expansion)?;
+        container = archive::entry_path(&container, entry_name);
+    }
+    Ok(bytes)
+}
+
+// 取出 class 条目并通过 CFR 反编译，返回源码
+pub fn decompile_entry(root: &Path, path: &str, cfr_jar: &Path, limits: &ArchiveLimits) -> ExtractResult<String> {
+    let bytes = extract_entry(root, path, limits)?;
+    if archive::detect_format(&bytes[..bytes.len().min(archive::SNIFF_LEN)], path) != Some(Format::Class) {
+        return Err(format!("{} 不是 class 文件", path).into());
+    }
+    decompile(cfr_jar, &bytes, &CancelToken::new())
+}
+
+// 在压缩包 container 中查找名为 entry_name 的条目
+fn read_entry<R: Read + Seek>(mut reader: R, container: &str, entry_name: &str, expansion: &mut Expansion) -> ExtractResult<Vec<u8>> {
+    let mut head = Vec::new();
+    reader.by_ref().take(archive::SNIFF_LEN as u64).read_to_end(&mut head)?;
+    reader.seek(SeekFrom::Start(0))?;
+    let format = archive::detect_format(&head, container);
+    read_entry_as(reader, format, container, entry_name, expansion)
+}
+
+fn read_entry_as<R: Read + Seek>(mut reader: R, format: Option<Format>, container: &str, entry_name: &str, expansion: &mut Expansion) -> ExtractResult<Vec<u8>> {
+    if expands(format) {
+        expansion.enter_archive()?;
+    }
+    let name = archive::entry_path(container, entry_name);
+    match format {
+        Some(Format::Zip) => read_zip_entry(reader, entry_name, &name, expansion),
+        Some(Format::SevenZ) => read_7z_entry(reader, entry_name, &name, expansion),
+        Some(Format::Tar) => read_tar_entry(reader, entry_name, &name, expansion),
+        // .tar.gz 等先解压，再在解压出的内容中查找
+        Some(Format::Compressed(compression)) => {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            let compressed_size = bytes.len() as u64;
+            let limit = expansion.limits.read_limit(expansion.expanded, compressed_size);
+            let decompressed_data = compression.decompress(&bytes, limit).map_err(|e| format!("解压失败: {}", e))?;
+            expansion.check(&decompressed_data, container, compressed_size)?;
+            let format = archive::detect_decompressed_format(&decompressed_data, container);
+            read_entry_as(Cursor::new(decompressed_data), format, container, entry_name, expansion)
+        }
+        Some(Format::Class | Format::Android(_)) | None => Err(format!("{} 不是压缩包", container).into()),
+    }
+    .map_err(|e| format!("{}: {}", name, e).into())
+}
+
+// name 为条目的完整路径，用于识别格式
+fn read_zip_entry<R: Read + Seek>(reader: R, entry_name: &str, name: &str, expansion: &mut Expansion) -> ExtractResult<Vec<u8>> {
+    let mut archive = ZipArchive::new(reader)?;
+    for i in 0..archive.len() {
+        if archive::decode_entry_name(archive.by_index_raw(i)?.name_raw()) == entry_name {
+            let mut file = archive.by_index(i)?;
+            let compressed_size = file.compressed_size();
+            return expansion.read(&mut file, name, compressed_size);
+        }
+    }
+    Err("压缩包中没有该条目".into())
+}
+
+fn read_7z_entry<R: Read + Seek>(mut reader: R, entry_name: &str, name: &str, expansion: &mut Expansion) -> ExtractResult<Vec<u8>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let archive = sevenz_rust::Archive::read(&mut reader, len, &[])?;
+    for folder_index in 0..archive.folders.len() {
+        let decoder = BlockDecoder::new(folder_index, &archive, &[], &mut reader);
+        if !decoder.entries().iter().any(|entry| entry.name == entry_name) {
+            continue;
+        }
+        // 固实压缩的条目需要按顺序解压，前面的条目读出后丢弃
+        let mut found = None;
+        decoder.for_each_entries(&mut |entry, entry_reader| {
+            // 超出限制时中止解压，错误在外面返回
+            let result = if entry.name == entry_name {
+                expansion.read(entry_reader, name, entry.compressed_size).map(Some)
+            } else {
+                expansion.skip(entry_reader).map(|()| None)
+            };
+            match result.transpose() {
+                None => Ok(true),
+                result => {
+                    found = result;
+                    Ok(false)
+                }
+            }
+        })?;
+        if let Some(result) = found {
+            return result;
+        }
+    }
+    Err("压缩包中没有该条目".into())
+}
+
+// tar 没有目录，按顺序读到该条目为止
+fn read_tar_entry<R: Read>(reader: R, entry_name: &str, name: &str, expansion: &mut Expansion) -> ExtractResult<Vec<u8>> {
+    let mut tar = TarReader::new(reader);
+    while let Some(entry) = tar.next_entry()? {
+        if entry.is_file && entry.name == entry_name {
+            return expansion.read(&mut tar.entry_data(), name, entry.size);
+        }
+    }
+    Err("压缩包中没有该条目".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::{zip_bytes, TempDir};
+    use crate::compliance::PHONE_RULE;
+    use crate::rule::{Rule, RuleSet};
+    use crate::scanner::{ScanOptions, Scanner};
+
+    #[test]
+    fn extraction_refuses_entries_beyond_limits() {
+        let dir = TempDir::create();
+        let inner = zip_bytes(&[("c.txt", b"13700137000")]);
+        let big = vec![b'x'; 5000];
+        dir.write("app.zip", zip_bytes(&[("inner.zip", &inner), ("big.txt", &big)]));
+        let limits = ArchiveLimits::default();
+        assert_eq!(extract_entry(&dir.path, "app.zip!/inner.zip!/c.txt", &limits).unwrap(), b"13700137000");
+
+        let shallow = ArchiveLimits { max_depth: 1, ..limits };
+        let error = extract_entry(&dir.path, "app.zip!/inner.zip!/c.txt", &shallow).unwrap_err().to_string();
+        assert!(error.ends_with("压缩包嵌套超过 1 层，拒绝提取"), "{}", error);
+        let small = ArchiveLimits { max_entry_size: 4096, ..limits };
+        let error = extract_entry(&dir.path, "app.zip!/big.txt", &small).unwrap_err().to_string();
+        assert!(error.ends_with("解压后超过 4096 字节，拒绝提取"), "{}", error);
+    }
+
+    #[test]
+    fn reported_entry_paths_can_be_extracted() {
+        let dir = TempDir::create();
+        let jar = zip_bytes(&[("conf/app.properties", b"phone=13800138000\n")]);
+        let war = dir.write("dist/app.war", zip_bytes(&[("WEB-INF/lib/x.jar", &jar)]));
+        let scanner = Scanner::new(RuleSet::new(vec![Rule::new(PHONE_RULE)]), ScanOptions { cfr_jar: None, ..ScanOptions::default() });
+        let limits = ArchiveLimits::default();
+        // 扫描目录时路径相对于目录，扫描单个文件时相对于其所在目录
+        for root in [dir.path.clone(), war] {
+            let report = scanner.scan_path(&root);
+            assert_eq!(report.findings.len(), 1);
+            let path = &report.findings[0].file_name;
+            assert!(path.ends_with("app.war!/WEB-INF/lib/x.jar!/conf/app.properties"), "{}", path);
+            assert_eq!(extract_entry(&root, path, &limits).unwrap(), b"phone=13800138000\n");
+        }
+    }
+}
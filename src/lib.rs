@@ -1,218 +1,316 @@
 
 use std::error::Error;
 use std::env;
-use pcre2::bytes::Regex;
-use encoding_rs::GBK;
-
-use std::sync::Arc;
-use tokio::sync::{Semaphore,Mutex};
-use tokio;
+use std::time::{Duration, Instant};
+use blob::find_blobs;
+use std::sync::OnceLock;
+use engine::{Limits, Matcher};
+use normalize::{normalize, Decoded, Normalized};
+use rule::{CompiledRuleSet, RuleSet};
+
+pub mod aggregate;
+pub mod android;
+pub mod archive;
+pub mod blob;
+pub mod builtin;
+pub mod compliance;
+pub mod engine;
+pub mod extract;
+pub mod mask;
+pub mod normalize;
+pub mod redact;
+pub mod rule;
+pub mod scanner;
+pub mod selftest;
+
+pub use scanner::{ArchiveLimits, CancelToken, ScanEvent, ScanOptions, ScanProgress, ScanReport, ScanSummary, Scanner};
 
 #[derive(Debug, Clone)]
 pub struct MatchResult {
+    pub rule: String,  // 命中的规则
     pub matched_text: String,
     pub file_name: String,
     pub line_number: String,
     pub origin_text: String,
+    pub decoded: Option<Decoded>,  // 匹配值经过解码时，记录解码过程及解码后的值
+    pub issue: Option<String>,     // 该行匹配中止的原因，不为空时该记录不是命中
 }
 
-pub async fn search_in_file_contents_sync(res : Arc<Mutex<Vec<MatchResult>>>, regex_list: Arc<Vec<String>>, contents: &str, file_name: &str) {
-    let re_list = Arc::clone(&regex_list);
-    for query in re_list.iter() {
-        let mut query_regex = query.clone();
-        // let handle = handles.clone();
-        // 这里替换了发布包扫描默认规则库第一条，对于class代码扫描时强制启用引号检测
-        if file_name.ends_with(".class") | file_name.ends_with(".java"){
-            if query == r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\s?[\"\']?(=|:)+\s?[\"\']?[a-zA-Z0-9\@\.]+[\"\']?"# {
-                query_regex = String::from(r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\s?[\"\']?(=|:)+\s?[\"\']+[a-zA-Z0-9\@\.]+[\"\']+"#);
-            }
-            
-        }
-        let config = Config {
-            query: query_regex,
-            contents: contents.to_string(),
-            ignore_case: false,
-        };
-        let file_name_clone = String::from(file_name);
-        let res_clone = res.clone();
-        // let a: &Vec<tokio::task::JoinHandle<()>> = &*handles.borrow_mut().borrow();
-        {
-            
-            if let Ok(matches) = run(config).await {
-                for (line_number, matched_text, origin_text) in matches{
-
-                    {
-                        let mut m = res_clone.lock().await;
-                        m.push(MatchResult {
-                            file_name: file_name_clone.clone(),
-                            line_number,
-                            matched_text,
-                            origin_text
-                            }   
-                        );
-                    }
-                }
+impl MatchResult {
+    pub fn is_issue(&self) -> bool {
+        self.issue.is_some()
+    }
+
+    // 匹配中止的说明，如 "规则 X 在 app.log 第 3 行匹配中止：超出匹配次数限制"
+    pub fn issue_message(&self) -> Option<String> {
+        self.issue.as_ref().map(|issue| {
+            // 与具体行无关的记录，如压缩包超出解压限制
+            if self.line_number.is_empty() {
+                return format!("{} {}：{}", self.rule, self.file_name, issue);
             }
-            
+            format!("规则 {} 在 {} 第 {} 行匹配中止：{}", self.rule, self.file_name, self.line_number, issue)
+        })
+    }
+
+    // 结果所在位置，如 "app.log 第 3 行"，与具体行无关时只有文件名
+    pub fn location(&self) -> String {
+        if self.line_number.is_empty() {
+            self.file_name.clone()
+        } else {
+            format!("{} 第 {} 行", self.file_name, self.line_number)
         }
     }
 }
 
+// (行号, 匹配值, 上下文, 解码信息, 匹配中止原因)
+type LineMatch = (String, String, String, Option<Decoded>, Option<String>);
 
-pub async fn search_in_file_contents(res : Arc<Mutex<Vec<MatchResult>>>,handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,  semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, contents: &str, file_name: &str) {
+// 按规则集匹配文件内容，file_name 用于判断规则作用范围及记录在结果中。
+// 每次调用都会编译规则，匹配多个文件时应先 RuleSet::compile，再逐个文件调用 search_timed
+pub fn search_in_file_contents(rule_set: &RuleSet, contents: &str, file_name: &str) -> Vec<MatchResult> {
+    search_timed(&rule_set.compile(), contents, file_name).0
+}
 
-    let re_list = Arc::clone(&regex_list);
-    
-    for query in re_list.iter() {
-        let mut query_regex = query.clone();
-        // 这里替换了发布包扫描默认规则库第一条，对于class代码扫描时强制启用引号检测
-        if file_name.ends_with(".class") | file_name.ends_with(".java"){
-            if query == r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\s?[\"\']?(=|:)+\s?[\"\']?[a-zA-Z0-9\@\.]+[\"\']?"# {
-                query_regex = String::from(r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\s?[\"\']?(=|:)+\s?[\"\']+[a-zA-Z0-9\@\.]+[\"\']+"#);
-            }
-            
+// 用编译好的规则匹配文件内容，另外返回每条规则的匹配耗时
+pub fn search_timed(rule_set: &CompiledRuleSet, contents: &str, file_name: &str) -> (Vec<MatchResult>, Vec<(String, Duration)>) {
+    // 规则可按文件路径限定作用范围，并为特定文件类型配置替代正则
+    let applicable: Vec<_> = rule_set
+        .rules
+        .iter()
+        .filter(|compiled| compiled.rule.applies_to(file_name))
+        .map(|compiled| (&compiled.rule, compiled.matcher_for(file_name)))
+        .collect();
+    let matchers: Vec<(&Matcher, &str)> = applicable
+        .iter()
+        .filter_map(|(_, (query, matcher))| matcher.ok().map(|matcher| (matcher, *query)))
+        .collect();
+    let mut searched = search_rules(&matchers, contents, rule_set.decode_depth).into_iter();
+
+    let mut results = Vec::new();
+    let mut timings = Vec::new();
+    for (rule, (_, matcher)) in applicable {
+        // 规则无法编译时，与单行匹配中止一样记录为一条结果，不静默丢弃
+        if let Err(e) = matcher {
+            results.push(MatchResult {
+                rule: rule.pattern.clone(),
+                file_name: file_name.to_string(),
+                line_number: String::new(),
+                matched_text: String::new(),
+                origin_text: String::new(),
+                decoded: None,
+                issue: Some(format!("匹配失败：{}", e)),
+            });
+            timings.push((rule.pattern.clone(), Duration::ZERO));
+            continue;
         }
-        let config = Config {
-            query: query_regex,
-            contents: contents.to_string(),
-            ignore_case: false,
-        };
-        let file_name_clone = String::from(file_name);
-        let res_clone = res.clone();
-        {
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
-            //handles_ref.push(
-            let handle = tokio::spawn(async move {
-            if let Ok(matches) = run(config).await {
-                for (line_number, matched_text, origin_text) in matches{
-
-                    {
-                        let mut m = res_clone.lock().await;
-                        m.push(MatchResult {
-                            file_name: file_name_clone.clone(),
-                            line_number,
-                            matched_text,
-                            origin_text
-                            }   
-                        );
-                    }
-                }
+        let (matches, elapsed) = searched.next().unwrap_or_default();
+        for (line_number, matched_text, origin_text, decoded, issue) in matches {
+            results.push(MatchResult {
+                rule: rule.pattern.clone(),
+                file_name: file_name.to_string(),
+                line_number,
+                matched_text,
+                origin_text,
+                decoded,
+                issue,
+            });
+        }
+        timings.push((rule.pattern.clone(), elapsed));
+    }
+    (results, timings)
+}
+
+// 用编译好的正则逐行匹配单条规则，query 为该正则的原文，用于内置规则的二次校验
+fn search(matcher: &Matcher, query: &str, contents: &str, decode_depth: usize) -> Vec<LineMatch> {
+    search_rules(&[(matcher, query)], contents, decode_depth).pop().map(|(matches, _)| matches).unwrap_or_default()
+}
+
+// 逐行匹配多条规则，每行只归一化及解码一次，各规则共用；返回每条规则的命中及匹配耗时
+fn search_rules(matchers: &[(&Matcher, &str)], contents: &str, decode_depth: usize) -> Vec<(Vec<LineMatch>, Duration)> {
+    let mut searched = vec![(Vec::new(), Duration::ZERO); matchers.len()];
+    let lines: Vec<&str> = contents.lines().collect();
+    for (index, line) in lines.iter().enumerate() {
+        // 先解码转义再匹配，命中位置映射回原文
+        let normalized = normalize(line);
+        let blobs = if decode_depth > 0 { line_blobs(line, &normalized, decode_depth) } else { Vec::new() };
+        let mut combined_text = None;
+        for ((matcher, query), (matches, elapsed)) in matchers.iter().zip(searched.iter_mut()) {
+            let start = Instant::now();
+            // 单行匹配中止（如超出回溯限制）时记录该行，继续匹配后面的行
+            let (found, issue) = match search_line(matcher, query, line, &normalized, &blobs) {
+                Ok(found) => (found, None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            };
+            *elapsed += start.elapsed();
+            if found.is_empty() && issue.is_none() {
+                continue;
+            }
+            // 获取上一行和下一行
+            let combined_text: &String = combined_text.get_or_insert_with(|| {
+                let prev_line = if index > 0 { lines[index - 1] } else { "" };
+                let next_line = if index < lines.len() - 1 { lines[index + 1] } else { "" };
+                format!("{}\r\n{}\r\n{}", prev_line, line, next_line)
+            });
+            for (match_str, decoded) in found {
+                matches.push(((index + 1).to_string(), match_str, combined_text.clone(), decoded, None));
             }
-            drop(permit); // 释放许可
-            });//);
-            {
-                let mut hs = handles.lock().await;
-                hs.push(handle);
+            if issue.is_some() {
+                matches.push(((index + 1).to_string(), String::new(), combined_text.clone(), None, issue));
             }
         }
     }
-
+    searched
 }
 
-async fn run(config: Config) -> Result<Vec<(String, String, String)>, Box<dyn Error + Send + Sync>> {
-    match search(&config.query, &config.contents).await {
-        Ok(result) => Ok(result),
-        Err(_) => {
-            // 尝试使用GBK编码重新匹配
-            let gbk_encoded = GBK.encode(&config.contents).0;
-            let gbk_contents = String::from_utf8_lossy(gbk_encoded.as_ref()).to_string();
-            search(&config.query, &gbk_contents).await
+// 单行命中：(匹配值, 解码信息)
+type LineHit = (String, Option<Decoded>);
+
+// 匹配单行，normalized 为该行解码转义后的文本，blobs 为其中解码出的编码片段，返回该行的所有命中
+fn search_line(matcher: &Matcher, query: &str, line: &str, normalized: &Normalized, blobs: &[(String, DecodedBlob)]) -> Result<Vec<LineHit>, Box<dyn Error + Send + Sync>> {
+    let subject = normalized.text.as_str();
+    let mut found: Vec<LineHit> = Vec::new();
+    // 单行超时覆盖整行，包括其中解码出的编码片段
+    let deadline = matcher.deadline();
+    // 同一行中的每个命中分别记录，如一行中的多个手机号
+    for (m_start, m_end) in matcher.find_all_until(subject.as_bytes(), deadline)? {
+        if check_match(query, subject, m_start, m_end)? {
+            let (start, end) = normalized.original_range(m_start, m_end);
+            // 根据捕获的起始和结束位置获取匹配的字符串
+            let match_str = String::from_utf8_lossy(&line.as_bytes()[start..end]).to_string();
+            let decoded_str = String::from_utf8_lossy(&subject.as_bytes()[m_start..m_end]).to_string();
+            let decoded = if normalized.is_changed() && decoded_str != match_str {
+                Some(Decoded {
+                    chain: normalized.decoders_in(m_start, m_end),
+                    text: decoded_str,
+                })
+            } else {
+                None
+            };
+            found.push((match_str, decoded));
         }
     }
+
+    // 编码片段中的命中，匹配值为原文中的编码片段
+    for (match_str, blob) in blobs {
+        for decoded in search_blob(matcher, query, blob, deadline)? {
+            found.push((match_str.clone(), Some(decoded)));
+        }
+    }
+    Ok(found)
 }
 
-async fn search<'a>(query: &str, contents: &'a str) -> Result<Vec<(String, String, String)>, Box<dyn Error + Send + Sync>> {
-    let regex = Regex::new(query)?;
-    let mut matches = vec![];
-    let lines: Vec<&str> = contents.lines().collect();
-    for (index, line) in contents.lines().enumerate() {
-        if let Some(caps) = regex.captures(line.as_bytes())? {
-            if let Some(m) = caps.get(0) { // 获取第一个捕获组
-                if query == r"[a-zA-Z0-9\*]+\@[a-zA-Z0-9]+\.[a-zA-Z]+" {
-                    if let Some(fa) = Regex::new(r"[a-zA-Z0-9]+\@[a-zA-Z0-9]+\.[a-zA-Z]+")?.captures(line.as_bytes())? {
-                        if let Some(f) = fa.get(0) {
-                            if f != m {
-                                continue;
-                            }
-                        }
-                        
-                    } else {
-                        continue;
-                    }
-                } else if query == r"(?<!\d)(\d{17}[Xx]|\d{18})(?!\d)" {
-                    // 身份证
-                    let start = m.start();
-                    let end = m.end();
-                    let idcard = String::from_utf8_lossy(&line.as_bytes()[start..end]).to_string();
-                    let y:u32 = idcard.clone()[6..10].parse().unwrap();
-                    if y < 1900 || y > 2025 {
-                        continue;
-                    }
-                    let m:u32 = idcard.clone()[10..12].parse().unwrap();
-                    if m < 1 || m > 12 {
-                        continue;
-                    }
-                    let d:u32 = idcard.clone()[12..14].parse().unwrap();
-                    if d < 1 || d > 31 {
-                        continue;
-                    }
-                    // 将身份证号码的前17位转换为数字
-                    let mut digits = Vec::new();
-                    for c in idcard.chars().take(17) {
-                        if let Some(digit) = c.to_digit(10) {
-                            digits.push(digit);
-                        } else {
-                            panic!("Invalid character in ID card");
-                        }
-                    }
-
-                    // 计算权重乘积之和
-                    // 计算权重乘积之和
-                    let mut weight_sum: u32 = 0;
-                    let weights = [7,9,10,5,8,4,2,1,6,3,7,9,10,5,8,4,2];
-
-                    for (i,d) in digits.iter().enumerate() {
-                        weight_sum += d * weights[i];
-                    };
-                    let checksum: u32 = (12 - (weight_sum % 11)) % 11;
-                    
-                    let mut result: bool = false;
-                    let last_digit: char = idcard.as_bytes()[17] as char;
-                    if checksum == 10 {
-                        if last_digit == 'X' || last_digit == 'x' {
-                            result = true;
-                        }
-                    } else {
-                        if last_digit.to_string() == checksum.to_string() {
-                            result = true;
-                        }
-                    }
-                    if !result {
-                        continue;
-                    }
-                        
-                }
+// 解码后的 Base64/十六进制片段，lines 为其中的每一行及该行继续解码出的片段，解码层数用完时为空
+struct DecodedBlob {
+    chain: Vec<String>,
+    lines: Vec<(String, Vec<DecodedBlob>)>,
+}
 
-                let start = m.start();
-                let end = m.end();
-                // 根据捕获的起始和结束位置获取匹配的字符串
-                let match_str = String::from_utf8_lossy(&line.as_bytes()[start..end]).to_string();
+// 解码一行中的编码片段，返回 (原文中的编码片段, 解码结果)，depth 为最大解码层数
+fn line_blobs(line: &str, normalized: &Normalized, depth: usize) -> Vec<(String, DecodedBlob)> {
+    find_blobs(&normalized.text)
+        .into_iter()
+        .map(|blob| {
+            let (start, end) = normalized.original_range(blob.start, blob.end);
+            let match_str = String::from_utf8_lossy(&line.as_bytes()[start..end]).to_string();
+            let mut chain = normalized.decoders_in(blob.start, blob.end);
+            chain.push(blob.kind.to_string());
+            (match_str, decode_blob(&blob.text, chain, depth))
+        })
+        .collect()
+}
 
-                // 获取上一行和下一行
-                let prev_line = if index > 0 { lines[index - 1] } else { "" };
-                let next_line = if index < lines.len() - 1 { lines[index + 1] } else { "" };
-                let combined_text = format!(
-                    "{}\r\n{}\r\n{}",
-                    prev_line,
-                    line,
-                    next_line
-                );
-                matches.push(((index + 1).to_string(), match_str, combined_text));
+// depth 为剩余的解码层数，大于 1 时继续解码其中的编码片段
+fn decode_blob(text: &str, chain: Vec<String>, depth: usize) -> DecodedBlob {
+    let lines = text
+        .lines()
+        .map(|line| {
+            let mut nested = Vec::new();
+            if depth > 1 {
+                for blob in find_blobs(line) {
+                    let mut nested_chain = chain.clone();
+                    nested_chain.push(blob.kind.to_string());
+                    nested.push(decode_blob(&blob.text, nested_chain, depth - 1));
+                }
+            }
+            (line.to_string(), nested)
+        })
+        .collect();
+    DecodedBlob { chain, lines }
+}
+
+// 在解码后的片段中匹配规则
+fn search_blob(matcher: &Matcher, query: &str, blob: &DecodedBlob, deadline: Option<Instant>) -> Result<Vec<Decoded>, Box<dyn Error + Send + Sync>> {
+    let mut found = Vec::new();
+    for (line, nested) in &blob.lines {
+        for (start, end) in matcher.find_all_until(line.as_bytes(), deadline)? {
+            if check_match(query, line, start, end)? {
+                found.push(Decoded {
+                    chain: blob.chain.clone(),
+                    text: String::from_utf8_lossy(&line.as_bytes()[start..end]).to_string(),
+                });
             }
         }
+        for nested in nested {
+            found.extend(search_blob(matcher, query, nested, deadline)?);
+        }
     }
+    Ok(found)
+}
+
 
-    Ok(matches)
+// 邮箱规则的二次校验正则，只编译一次
+fn unmasked_email() -> &'static Matcher {
+    static MATCHER: OnceLock<Matcher> = OnceLock::new();
+    MATCHER.get_or_init(|| Matcher::new(r"[a-zA-Z0-9]+\@[a-zA-Z0-9]+\.[a-zA-Z]+", &Limits::default()).expect("内置正则应能编译"))
+}
+
+// 对部分内置规则的命中值做二次校验，如邮箱需未被掩码、身份证需满足日期及校验位
+pub fn check_match(query: &str, line: &str, start: usize, end: usize) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    if query == r"[a-zA-Z0-9\*]+\@[a-zA-Z0-9]+\.[a-zA-Z]+" {
+        // 从命中处开始查找，同一行中有多个邮箱时逐个校验
+        if let Some((f_start, f_end)) = unmasked_email().find_at(line.as_bytes(), start)? {
+            if f_start != start || f_end != end {
+                return Ok(false);
+            }
+        } else {
+            return Ok(false);
+        }
+    } else if query == r"(?<!\d)(\d{17}[Xx]|\d{18})(?!\d)" {
+        // 身份证，命中值不是 18 位 ASCII 数字（末位可为 X）时不符合
+        let Some(idcard) = line.get(start..end) else {
+            return Ok(false);
+        };
+        let digits: Vec<u32> = idcard.chars().map_while(|c| c.to_digit(10)).collect();
+        let last_digit = match idcard.chars().nth(17) {
+            Some(c) if idcard.chars().count() == 18 && digits.len() >= 17 => c,
+            _ => return Ok(false),
+        };
+        let field = |from: usize, to: usize| digits[from..to].iter().fold(0, |n, d| n * 10 + d);
+        if !(1900..=2025).contains(&field(6, 10)) {
+            return Ok(false);
+        }
+        if !(1..=12).contains(&field(10, 12)) {
+            return Ok(false);
+        }
+        if !(1..=31).contains(&field(12, 14)) {
+            return Ok(false);
+        }
+
+        // 计算前17位的权重乘积之和
+        let weights = [7,9,10,5,8,4,2,1,6,3,7,9,10,5,8,4,2];
+        let weight_sum: u32 = digits.iter().zip(weights).map(|(d, w)| d * w).sum();
+        let checksum: u32 = (12 - (weight_sum % 11)) % 11;
+
+        let result = if checksum == 10 {
+            last_digit == 'X' || last_digit == 'x'
+        } else {
+            last_digit.to_digit(10) == Some(checksum)
+        };
+        if !result {
+            return Ok(false);
+        }
+    }
+    Ok(true)
 }
 
 
@@ -221,6 +319,8 @@ pub struct Config {
     // pub file_path: String,
     pub contents: String,  // 添加一个字段用于存储文件内容
     pub ignore_case: bool,
+    pub decode_depth: usize,  // Base64/十六进制片段解码重扫层数，0 表示不解码
+    pub limits: Limits,       // 匹配次数、回溯深度及单行时间限制
 }
 
 impl Config {
@@ -242,7 +342,7 @@ impl Config {
 
         let ignore_case = env::var("IGNORE_CASE").is_ok();
 
-        Ok(Config { query,contents,ignore_case })
+        Ok(Config { query,contents,ignore_case,decode_depth: 0,limits: Limits::default() })
     }
 }
 
@@ -323,4 +423,93 @@ pub fn search_case_insensitive<'a>(
 //         );
 //     }
     
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compliance::{EMAIL_RULE, IDCARD_RULE, PHONE_RULE};
+    use rule::Rule;
+
+    fn check(query: &str, line: &str) -> bool {
+        let (start, end) = Matcher::new(query, &Limits::default()).unwrap().find(line.as_bytes()).unwrap().unwrap();
+        check_match(query, line, start, end).unwrap()
+    }
+
+    #[test]
+    fn idcard_requires_valid_date_and_checksum() {
+        assert!(check(IDCARD_RULE, "id=11010519491231002X"));
+        assert!(check(IDCARD_RULE, "id=11010519491231002x"));
+        assert!(!check(IDCARD_RULE, "id=110105194912310021"));
+        assert!(!check(IDCARD_RULE, "id=110105189912310026"));
+        assert!(!check(IDCARD_RULE, "id=110105194913310021"));
+        assert!(!check(IDCARD_RULE, "id=110105194912320021"));
+    }
+
+    #[test]
+    fn email_must_be_unmasked() {
+        assert!(check(EMAIL_RULE, "mail bob@example.com"));
+        assert!(!check(EMAIL_RULE, "mail b**@example.com, bob@example.com"));
+        // 其他规则不做二次校验
+        assert!(check(PHONE_RULE, "13800138000"));
+    }
+
+    #[test]
+    fn search_reports_every_hit_with_context() {
+        let rule_set = RuleSet::new(vec![Rule::new(PHONE_RULE)]);
+        let results = search_in_file_contents(&rule_set, "a\n13800138000 13900139000\nb", "app.log");
+        let values: Vec<&str> = results.iter().map(|r| r.matched_text.as_str()).collect();
+        assert_eq!(values, vec!["13800138000", "13900139000"]);
+        assert_eq!(results[0].line_number, "2");
+        assert_eq!(results[0].origin_text, "a\r\n13800138000 13900139000\r\nb");
+        assert!(results[0].decoded.is_none());
+    }
+
+    #[test]
+    fn search_decodes_escapes() {
+        let rule_set = RuleSet::new(vec![Rule::new(PHONE_RULE)]);
+        let results = search_in_file_contents(&rule_set, r"tel=\u00313800138000", "app.log");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_text, r"\u00313800138000");
+        assert_eq!(results[0].decoded.as_ref().unwrap().to_string(), "unicode → \"13800138000\"");
+    }
+
+    #[test]
+    fn search_decodes_blobs_up_to_depth() {
+        let mut rule_set = RuleSet::new(vec![Rule::new(PHONE_RULE)]);
+        assert!(search_in_file_contents(&rule_set, "v=cGhvbmU9MTM4MDAxMzgwMDA=", "app.log").is_empty());
+        rule_set.decode_depth = 1;
+        let results = search_in_file_contents(&rule_set, "v=cGhvbmU9MTM4MDAxMzgwMDA=", "app.log");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_text, "cGhvbmU9MTM4MDAxMzgwMDA=");
+        assert_eq!(results[0].decoded.as_ref().unwrap().chain, vec!["base64"]);
+    }
+
+    #[test]
+    fn nested_blobs_are_decoded_once_for_all_rules() {
+        let line = "x=ZGF0YT1jR2h2Ym1VOU1UTTRNREF4TXpnd01EQWdiV0ZwYkQxaWIySkFaWGhoYlhCc1pTNWpiMjA9 13900139000";
+        let mut rule_set = RuleSet::new(vec![Rule::new(PHONE_RULE), Rule::new(EMAIL_RULE)]);
+        rule_set.decode_depth = 1;
+        assert_eq!(search_in_file_contents(&rule_set, line, "app.log").len(), 1);
+        rule_set.decode_depth = 2;
+        let results = search_in_file_contents(&rule_set, line, "app.log");
+        // 结果仍按规则顺序排列
+        let found: Vec<(&str, &str)> = results
+            .iter()
+            .map(|r| (r.rule.as_str(), r.decoded.as_ref().map_or(r.matched_text.as_str(), |d| d.text.as_str())))
+            .collect();
+        assert_eq!(found, vec![(PHONE_RULE, "13900139000"), (PHONE_RULE, "13800138000"), (EMAIL_RULE, "bob@example.com")]);
+        assert_eq!(results[1].decoded.as_ref().unwrap().chain, vec!["base64", "base64"]);
+        let (_, timings) = search_timed(&rule_set.compile(), line, "app.log");
+        assert_eq!(timings.len(), 2);
+    }
+
+    #[test]
+    fn invalid_rule_is_reported_as_issue() {
+        let rule_set = RuleSet::new(vec![Rule::new("(")]);
+        let results = search_in_file_contents(&rule_set, "text", "app.log");
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_issue());
+        assert_eq!(results[0].location(), "app.log");
+    }
+}
@@ -1,48 +1,41 @@
 #![windows_subsystem = "windows"]
 use std::{error::Error, path::PathBuf, vec};
-use minigrep::{search_in_file_contents, search_in_file_contents_sync, MatchResult};
+use minigrep::{CancelToken, MatchResult, ScanEvent, ScanOptions, ScanProgress, ScanReport, ScanSummary, Scanner};
+use minigrep::aggregate::aggregate;
+use minigrep::builtin::{builtin_rule, BuiltinRule, LOG_RULES, PACKAGE_RULES};
+use minigrep::compliance::{compliance_rules, summarize};
+use minigrep::mask::{mask_aggregated, mask_result, mask_results, HitIndex};
+use minigrep::normalize::normalize;
+use minigrep::redact::{redact_path, RedactManifest, RedactStyle, MANIFEST_FILE_NAME};
+use minigrep::engine::Limits;
+use minigrep::extract::{decompile_entry, extract_entry};
+use minigrep::android::AndroidFile;
+use minigrep::archive::ENTRY_SEPARATOR;
+use minigrep::scanner::DEFAULT_CFR_JAR;
+use minigrep::rule::{Rule, RuleConfig, RuleError, RuleSet, YamlConfig};
+use minigrep::selftest::test_rule;
 mod text;
 use std::fs;
 extern crate native_windows_gui as nwg;  
 use nwg::NativeUi;
 use clipboard_win::{formats,set_clipboard};
 use std::path::Path;
-use regex::Regex;
-use zip::read::ZipArchive;
-use std::io::{Cursor, Read, Write};
-use encoding_rs::GBK;
-use flate2::read::GzDecoder;
-use std::str::from_utf8;
-use std::process::{Stdio,Command};
-// use tokio::process::Command;
 
 use std::cell::RefCell;  
 use std::cell::Cell;
-use std::time::Instant;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 // 多线程
+use std::rc::Rc;
 use std::sync::Arc;
-use tokio::sync::{Semaphore,Mutex};
-use tokio::io::AsyncReadExt;
-// use tokio::task;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
 
-use serde::{Deserialize, Serialize};
 use serde_yaml;
 use dirs::home_dir;
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[derive(Serialize, Deserialize)]
-struct RuleConfig {
-    name: String,
-    enabled: bool,
-    patterns: Vec<String>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct YamlConfig {
-    rules: Vec<RuleConfig>,
-}
-
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum RuleState {
     Log,
@@ -63,22 +56,113 @@ impl Default for LineState {
         LineState::Line3
     }
 }
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum MaskState {
+    #[default]
+    Masked,
+    Raw,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum ResultState {
+    #[default]
+    Each,
+    Aggregate,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum BudgetState {
+    #[default]
+    Off,
+    On,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum DecodeState {
+    #[default]
+    Off,
+    On,
+}
+// 聚合展示时每组保留的位置示例数量
+const AGGREGATE_SAMPLE_LIMIT: usize = 10;
+// 开启编码解码扫描时 Base64/十六进制嵌套解码的最大层数
+const DECODE_DEPTH: usize = 3;
+// 开启单行超时保护时每条规则匹配单行的时间上限
+const LINE_BUDGET: Duration = Duration::from_secs(1);
+// 扫描报告弹窗中每类跳过/失败文件最多列出的条数，完整报告复制到剪贴板
+const REPORT_ISSUE_LIMIT: usize = 30;
+// 进度条的刻度数，按已扫描的字节数推进
+const PROGRESS_RANGE: u32 = 1000;
+
+// 逐条展示时的匹配值：经过解码才命中的值，同时展示解码过程；匹配中止的行展示中止原因
+fn result_value(result: &MatchResult) -> String {
+    match (&result.issue_message(), &result.decoded) {
+        (Some(issue), _) => issue.clone(),
+        (None, Some(decoded)) => format!("{} [{}]", result.matched_text, decoded),
+        (None, None) => result.matched_text.clone(),
+    }
+}
+
+// 扫描统计的一行摘要
+fn summary_line(summary: &ScanSummary) -> String {
+    let mut line = format!(
+        "{} 个文件，{:.1} MB，打开压缩包 {} 个，反编译 {} 个",
+        summary.files_visited,
+        summary.bytes_scanned as f64 / 1048576.0,
+        summary.archives_opened,
+        summary.classes_decompiled
+    );
+    if summary.limits_exceeded > 0 {
+        line.push_str(&format!("，超出解压限制 {} 处", summary.limits_exceeded));
+    }
+    line
+}
+
+// 扫描报告正文，limit 限制跳过/失败文件列出的条数
+fn scan_report_text(report: &ScanReport, limit: Option<usize>) -> String {
+    let summary = &report.summary;
+    let mut lines = vec![format!("{}，耗时 {:?}", summary_line(summary), summary.duration)];
+    if report.cancelled {
+        lines.push("检测被停止，统计只包含停止前的部分".to_string());
+    }
+    lines.push(String::new());
+    lines.push("各规则命中数及匹配耗时：".to_string());
+    for (rule, elapsed) in &summary.rule_time {
+        let count = summary.rule_counts.get(rule).copied().unwrap_or(0);
+        lines.push(format!("{}：{} 处，{:?}", rule, count, elapsed));
+    }
+    let mut file_counts: Vec<(&String, &usize)> = summary.file_counts.iter().collect();
+    file_counts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    lines.push(String::new());
+    lines.push(format!("有命中的文件 {} 个：", file_counts.len()));
+    for (file, count) in file_counts.iter().take(limit.unwrap_or(usize::MAX)) {
+        lines.push(format!("{}：{} 处", file, count));
+    }
+    for (title, issues) in [("跳过", &report.skipped), ("处理失败", &report.errors)] {
+        lines.push(String::new());
+        lines.push(format!("{}的文件 {} 个：", title, issues.len()));
+        for issue in issues.iter().take(limit.unwrap_or(usize::MAX)) {
+            lines.push(format!("{}：{}", issue.file_name, issue.reason));
+        }
+        if let Some(limit) = limit.filter(|&limit| issues.len() > limit) {
+            lines.push(format!("……等 {} 个", issues.len() - limit));
+        }
+    }
+    lines.join("\r\n")
+}
 impl BasicApp {
     // 重置为默认日志规则
     fn reset_to_default_log_rules(&self) {
-        self.load_rules_from_text(text::LOG_RULES);
+        self.load_rules_from_text(LOG_RULES);
     }
     // 重置为默认发布包规则
     fn reset_to_default_package_rules(&self) {
-        self.load_rules_from_text(text::PACKAGE_RULES);
+        self.load_rules_from_text(PACKAGE_RULES);
     }
     // 重置默认规则根据来源
-    fn load_rules_from_text(&self, rules: &[(&str, &[&str])]) {
+    fn load_rules_from_text(&self, rules: &[(&str, &[BuiltinRule])]) {
         for (i, &(_, patterns)) in rules.iter().enumerate() {
             if i < self.features.len() {
                 let feature = &self.features[i];
                 feature.list_box.clear();
-                for &pattern in patterns {
+                for &(pattern, _, _) in patterns {
                     feature.list_box.push(pattern.to_string());
                 }
             }
@@ -93,7 +177,7 @@ impl BasicApp {
     fn save_current_config(&self) -> Result<(), Box<dyn Error>> {
         let mut rules = Vec::new();
         for feature in &self.features {
-            let patterns: Vec<String> = feature.list_box.collection().to_vec();
+            let patterns: Vec<Rule> = feature.list_box.collection().iter().map(|p| self.get_rule(p)).collect();
             let enabled = feature.able_checkbox.check_state() == nwg::CheckBoxState::Checked;
             rules.push(RuleConfig {
                 name: format!("规则{}", feature.id),
@@ -101,7 +185,10 @@ impl BasicApp {
                 patterns,
             });
         }
-        let config = YamlConfig { rules };
+        let config = YamlConfig {
+            rules,
+            zip_passwords: self.zip_passwords.borrow().clone(),
+        };
         let config_content = serde_yaml::to_string(&config)?;
         fs::write(self.get_config_path(), config_content)?;
         Ok(())
@@ -154,12 +241,12 @@ impl FeatureLayout {
         // vec!["手机号","邮箱","身份证号","ipv4","密钥token"]
         match self.id {
             0 => {
-                for pattern in text::LOG_RULES[0].1 {
+                for (pattern, _, _) in LOG_RULES[0].1 {
                     self.list_box.push(pattern.to_string());
                 }
             },
             1 => {
-                for pattern in text::LOG_RULES[1].1 {
+                for (pattern, _, _) in LOG_RULES[1].1 {
                     self.list_box.push(pattern.to_string());
                 }
             },
@@ -169,20 +256,51 @@ impl FeatureLayout {
 }
 
 
+// 扫描线程发回界面线程的消息
+enum ScanMessage {
+    Event(ScanEvent),
+    Done(ScanReport),
+    Redacted(Result<RedactManifest, String>),
+}
+
+// 后台任务的种类，结束时按种类展示结果
+enum ScanTask {
+    Check,            // 检测，命中即时展示
+    Compliance,       // 脱敏合规检查，结束后按文件汇总
+    Redact(PathBuf),  // 脱敏导出到该目录，无法中途停止
+}
+
+// 后台进行中的检测
+struct RunningScan {
+    messages: mpsc::Receiver<ScanMessage>,
+    pending: Arc<AtomicBool>,  // 已通知界面但消息尚未取走，避免每个事件都发送一次通知
+    cancel: CancelToken,
+    worker: thread::JoinHandle<()>,
+    task: ScanTask,
+    rules: Vec<Rule>,
+    start: Instant,
+    progress: ScanProgress,
+    findings: usize,
+}
+
 #[derive(Default)]  // 自动为结构体实现 Default trait
 pub struct BasicApp {  // 定义一个名为 BasicApp 的公共结构体
     window: nwg::Window,  // 窗口组件
     layout: nwg::GridLayout,  // 网格布局管理器
 
     features: Vec<FeatureLayout>,
-    path_input_text: Arc<RefCell<nwg::TextInput>>,
+    path_input_text: Rc<RefCell<nwg::TextInput>>,
+    entry_input_text: nwg::TextInput,  // 取出压缩包条目时填写的条目路径
     filedialog: nwg::FileDialog,
+    redact_dialog: nwg::FileDialog,
+    extract_dialog: nwg::FileDialog,
     browse_button: nwg::Button,
     check_button: nwg::Button,
     clear_button: nwg::Button,
     list_view: nwg::ListView,
-    dyn_tis: Arc<RefCell<nwg::Label>>,
-    search_tis: Arc<RefCell<nwg::Label>>,
+    dyn_tis: Rc<RefCell<nwg::Label>>,
+    search_tis: Rc<RefCell<nwg::Label>>,
+    progress_bar: nwg::ProgressBar,
 
     menu_update: nwg::MenuItem,
     menu_about: nwg::MenuItem,
@@ -193,14 +311,39 @@ pub struct BasicApp {  // 定义一个名为 BasicApp 的公共结构体
     menu_reset_package: nwg::MenuItem,
     menu_switch_3_line: nwg::MenuItem,
     menu_switch_1_line: nwg::MenuItem,
+    menu_switch_aggregate: nwg::MenuItem,
+    menu_switch_each: nwg::MenuItem,
+    menu_switch_masked: nwg::MenuItem,
+    menu_switch_raw: nwg::MenuItem,
+    menu_redact: nwg::MenuItem,
+    menu_extract_entry: nwg::MenuItem,
+    menu_compliance: nwg::MenuItem,
+    menu_decode_on: nwg::MenuItem,
+    menu_decode_off: nwg::MenuItem,
+    menu_self_test: nwg::MenuItem,
+    menu_scan_report: nwg::MenuItem,
+    menu_budget_on: nwg::MenuItem,
+    menu_budget_off: nwg::MenuItem,
 
     event_handler: RefCell<Option<nwg::EventHandler>>,
-    origin_text: Arc<RefCell<nwg::RichTextBox>>,
-    origin_file: Arc<RefCell<nwg::TextInput>>,
+    scan_notice: nwg::Notice,
+    running_scan: RefCell<Option<RunningScan>>,
+    last_scan: RefCell<Option<ScanReport>>,  // 上次扫描的统计及跳过/失败的文件，不含命中
+    scan_root: RefCell<Option<PathBuf>>,     // 上次扫描的路径，结果中的文件名相对于该路径
+    origin_text: Rc<RefCell<nwg::RichTextBox>>,
+    origin_file: Rc<RefCell<nwg::TextInput>>,
     rich_text_font: nwg::Font,
 
     rule_state: Cell<RuleState>,
-    line_state: Arc<RefCell<LineState>>,
+    line_state: Rc<RefCell<LineState>>,
+    result_state: Cell<ResultState>,
+    mask_state: Cell<MaskState>,
+    decode_state: Cell<DecodeState>,
+    budget_state: Cell<BudgetState>,
+    // 配置文件中规则的附加配置（如脱敏方式），按正则索引
+    rule_options: RefCell<HashMap<String, Rule>>,
+    // 配置文件中的 zip 密码，扫描加密的 zip 条目时依次尝试
+    zip_passwords: RefCell<Vec<String>>,
 
     ico_capoo: nwg::Icon,
 }
@@ -209,6 +352,7 @@ impl BasicApp {
 
     // 设置规则（从配置文件）
     fn set_rules(&self,config: YamlConfig) {
+        *self.zip_passwords.borrow_mut() = config.zip_passwords.clone();
         for (i, rule) in config.rules.iter().enumerate() {
             if i < self.features.len() {
                 let feature = &self.features[i];
@@ -221,11 +365,40 @@ impl BasicApp {
                     }
                 );
                 for pattern in &rule.patterns {
-                    feature.list_box.push(pattern.clone());
+                    feature.list_box.push(pattern.pattern.clone());
+                    self.rule_options.borrow_mut().insert(pattern.pattern.clone(), pattern.clone());
                 }
             }
         }
-        return;
+        // 有错误的规则仍然载入，方便在界面中修改，但修正前不能开始搜索
+        let errors = config.validate(&self.limits());
+        if !errors.is_empty() {
+            self.show_rule_errors("配置文件中的规则有错误", &errors);
+        }
+    }
+
+    // 提示规则编译错误
+    fn show_rule_errors(&self, title: &str, errors: &[RuleError]) {
+        let content = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\r\n");
+        nwg::simple_message(title, &content);
+    }
+
+    // 检查启用的规则能否编译，有错误时提示并返回 false
+    fn check_enabled_rules(&self) -> bool {
+        let errors: Vec<RuleError> = self.features.iter()
+            .filter(|feature| feature.able_checkbox.check_state() == nwg::CheckBoxState::Checked)
+            .flat_map(|feature| {
+                feature.list_box.collection().iter().enumerate()
+                    .filter_map(|(i, pattern)| self.get_rule(pattern).validate(&format!("规则{} 第{}条", feature.id, i + 1), &self.limits()).err())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if errors.is_empty() {
+            return true;
+        }
+        self.dyn_tis.borrow_mut().set_text(&format!("有 {} 条启用的规则无法编译，修正后才能开始搜索", errors.len()));
+        self.show_rule_errors("规则错误", &errors);
+        false
     }
 
     fn initialize_defaults(&self) {
@@ -300,6 +473,12 @@ impl BasicApp {
                     let selected_text = feature.list_box.collection()[selected].clone();
                     feature.input_text.set_text(&selected_text);
                     feature.input_text.set_focus();
+                    // 显示该规则编译所用的引擎
+                    let name = format!("规则{} 第{}条", feature.id, selected + 1);
+                    match self.get_rule(&selected_text).validate(&name, &self.limits()) {
+                        Ok(engine) => self.dyn_tis.borrow_mut().set_text(&format!("{} 使用 {} 引擎", name, engine)),
+                        Err(e) => self.dyn_tis.borrow_mut().set_text(&e.to_string()),
+                    }
                 }
             }
         }
@@ -310,6 +489,15 @@ impl BasicApp {
         let feature = &self.features[feature_id];
         if let Some(selected) = feature.list_box.selection() {
             let edited_text = feature.input_text.text();
+            let name = format!("规则{} 第{}条", feature_id, selected + 1);
+            let engine = match self.get_rule(&edited_text).validate(&name, &self.limits()) {
+                Ok(engine) => engine,
+                Err(e) => {
+                    self.show_rule_errors("规则错误", &[e]);
+                    return;
+                }
+            };
+            self.dyn_tis.borrow_mut().set_text(&format!("{} 已修改，使用 {} 引擎", name, engine));
             let mut collection = feature.list_box.collection().clone(); // 获取并克隆当前的集合
             collection[selected] = edited_text.clone(); // 更新集合中的值
             feature.list_box.set_collection(collection); // 设置更新后的集合
@@ -347,541 +535,553 @@ impl BasicApp {
         .collect()
     }
 
-    // 获取文件进行判断
-    async fn get_file(&self,res: Arc<Mutex<Vec<MatchResult>>>, handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>, semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, path: Arc<PathBuf>, base_dir: Arc<&Path>) -> Result<(), Box<dyn Error>> {
-        let file_extension = path.extension().and_then(std::ffi::OsStr::to_str).unwrap_or("");
-        match file_extension {
-            "zip" => {
-            let file = tokio::fs::File::open(&*path).await?;
-                self.process_zip_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), file, &path, Arc::clone(&base_dir)).await?;
-            },
-            "war" => {
-                let file = tokio::fs::File::open(&*path).await?;
-                self.process_war_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), file, &path, Arc::clone(&base_dir)).await?;
-            },
-            "jar" => {
-                let file = tokio::fs::File::open(&*path).await?;
-                self.process_war_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), file, &path, Arc::clone(&base_dir)).await?;
-            },
-            "gz" => {
-                let file = tokio::fs::File::open(&*path).await?;
-                self.process_gz_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), file, &path, Arc::clone(&base_dir)).await?;
-            },
-            "tar" => {
-                let file = tokio::fs::File::open(&*path).await?;
-                self.process_tar_bytes(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), file, &path, Arc::clone(&base_dir)).await?;
-            },
-            "class" => {
-                let file = tokio::fs::File::open(&*path).await?;
-                self.process_class_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), file, &path, Arc::clone(&base_dir)).await?;
+    // 获取正则对应的规则，没有附加配置时使用默认规则
+    fn get_rule(&self, pattern: &str) -> Rule {
+        self.rule_options.borrow().get(pattern).cloned().unwrap_or_else(|| builtin_rule(pattern))
+    }
+
+    // 获取启用的规则列表
+    fn get_check_rules(&self) -> Vec<Rule> {
+        self.get_check_regex_list().iter().map(|p| self.get_rule(p)).collect()
+    }
+
+    // 编码片段解码重扫层数
+    fn decode_depth(&self) -> usize {
+        match self.decode_state.get() {
+            DecodeState::On => DECODE_DEPTH,
+            DecodeState::Off => 0,
+        }
+    }
+
+    // 默认匹配限制，规则可在配置文件中单独设置 match_limit、depth_limit
+    fn limits(&self) -> Limits {
+        Limits {
+            line_budget: match self.budget_state.get() {
+                BudgetState::On => Some(LINE_BUDGET),
+                BudgetState::Off => None,
             },
-            _ => {
-                let contents = match tokio::fs::read_to_string(&*path).await {
-                    Ok(c) => c,
-                    Err(_) => {
-                        let contents_gbk = match tokio::fs::read(&*path).await {
-                            Ok(bytes) => {
-                                let (cow, _, _) = GBK.decode(&bytes);
-                                cow.into_owned()
-                            },
-                            Err(_) => {
-                                self.dyn_tis.borrow_mut().set_text(format!("文件 {} 不是文本文件，跳过检索", &path.to_string_lossy()).as_str());
-                                return Ok(());// 如果失败，返回错误
-                            }
-                        };
-                        contents_gbk
-                    }  
-                };
-                search_in_file_contents(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), &contents, &self.strip_base_dir(*base_dir, &path)).await;
-            }
+            ..Limits::default()
         }
+    }
 
-        Ok(())
+    // 按界面上的解码及超时设置组成规则集，扫描、合规检查及脱敏导出共用
+    fn check_rule_set(&self, rules: Vec<Rule>) -> RuleSet {
+        RuleSet {
+            decode_depth: self.decode_depth(),
+            limits: self.limits(),
+            ..RuleSet::new(rules)
+        }
     }
-    
-    // 从文件夹内获取文件
-    async fn get_file_by_dir(&self,res: Arc<Mutex<Vec<MatchResult>>>, handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>, semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, path_dir: PathBuf, base_dir: Arc<&Path>) {
-        let feature = Box::pin(async move {
-            if path_dir.is_file(){
-                let _ = self.get_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), Arc::new(path_dir), Arc::clone(&base_dir)).await; 
+
+    fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            zip_passwords: self.zip_passwords.borrow().clone(),
+            ..ScanOptions::default()
+        }
+    }
+
+    // 提示跳过及失败的文件，并按行数设置处理命中的上下文
+    fn take_findings(&self, mut report: ScanReport) -> Vec<MatchResult> {
+        if !report.skipped.is_empty() || !report.errors.is_empty() {
+            let mut message = format!("跳过 {} 个文件，{} 个文件处理失败", report.skipped.len(), report.errors.len());
+            if let Some(error) = report.errors.first() {
+                message = format!("{}，如 {}：{}", message, error.file_name, error.reason);
+            }
+            message = format!("{}，完整列表见菜单“扫描报告”", message);
+            self.dyn_tis.borrow_mut().set_text(&message);
+        }
+
+        let findings = std::mem::take(&mut report.findings);
+        *self.last_scan.borrow_mut() = Some(report);
+        let mut results = Vec::new();
+        for x in findings {
+            if *self.line_state.borrow_mut() == LineState::Line3 {
+                results.push(x);
             } else {
-                match fs::read_dir(path_dir) {
-                    Ok(iopen) => {
-                        for entry in iopen {
-                            match entry {
-                                Ok(en_try) => {
-                                    let path = en_try.path();
-                                    if path.is_dir() {
-                                        // 如果是目录，则递归调用自身
-                                        self.get_file_by_dir(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), path, Arc::clone(&base_dir)).await;
-                                    } else {
-                                        // 如果是文件，则调用 get_file 方法处理
-                                        let _ = self.get_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore), Arc::clone(&regex_list), Arc::new(path), Arc::clone(&base_dir)).await; 
-                                    }
-                                },
-                                _ => {}
-                            }
-                            
-                        }
-                    },
-                    _ => {}
-                }
+                let origin_text = x.origin_text.split('\n').nth(1).unwrap_or_default().to_string();
+                results.push(MatchResult { origin_text, ..x });
             }
-        });
-        feature.await
+        }
+        results
     }
+
     
-    // 操作zip文件
-    async fn process_zip_file<R: AsyncReadExt + Unpin >(&self, res: Arc<Mutex<Vec<MatchResult>>>, handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,  semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, mut reader: R, zip_path: &Path, base_dir: Arc<&Path>) -> Result<(), Box<dyn Error>> {
-        let feature = Box::pin(async move {
-            let mut buffer = Vec::new();
-            reader.read_to_end(&mut buffer).await?;
-            let cursor = Cursor::new(buffer);
-            let mut archive = ZipArchive::new(cursor)?;
-            for i in 0..archive.len() {
-                let mut file = archive.by_index(i)?;
-                if file.is_file() {
-                    let file_name_bytes = file.name_raw();
-                    let (decoded_name, _, _) = GBK.decode(file_name_bytes);
-                    let file_name = decoded_name.to_string();
-                    
-                    let mut relative_path = self.strip_base_dir(*Arc::clone(&base_dir), zip_path);
-                    relative_path = format!("{}/{}", relative_path, file_name);  
-        
-                    if file_name.ends_with(".zip") {
-                        let mut nested_contents = Vec::new();
-                        file.read_to_end(&mut nested_contents)?;
-                        let cursor = Cursor::new(nested_contents);
-                        self.process_zip_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                    } else if file_name.ends_with(".gz") {
-                        let mut nested_contents = Vec::new();
-                        file.read_to_end(&mut nested_contents)?;
-                        let cursor = Cursor::new(nested_contents);
-                        self.process_gz_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                    } else if file_name.ends_with(".tar") {
-                        let mut nested_contents = Vec::new();
-                        file.read_to_end(&mut nested_contents)?;
-                        let cursor = Cursor::new(nested_contents);
-                        self.process_tar_bytes(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                    } else if file_name.ends_with(".war") {
-                        let mut nested_contents = Vec::new();
-                        file.read_to_end(&mut nested_contents)?;
-                        let cursor = Cursor::new(nested_contents);
-                        self.process_war_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                    } else if file_name.ends_with(".jar") {
-                        let mut nested_contents = Vec::new();
-                        file.read_to_end(&mut nested_contents)?;
-                        let cursor = Cursor::new(nested_contents);
-                        self.process_war_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                    } else if file_name.ends_with(".class") {
-                        let mut nested_contents = Vec::new();
-                        file.read_to_end(&mut nested_contents)?;
-                        let cursor = Cursor::new(nested_contents);
-                        self.process_class_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                    } else {
+    // 检测按钮点击后
+    fn begin_check(&self) {
+        if self.is_busy() {
+            return;
+        }
+        self.list_view.clear();
+        let directory = self.path_input_text.borrow().text();
+        if directory.is_empty() {
+            self.path_input_text.borrow().set_text("请输入日志目录");
+            return;
+        }
+        if !self.check_enabled_rules() {
+            return;
+        }
+        self.search_tis.borrow_mut().set_text("搜索中...");
+        *self.scan_root.borrow_mut() = Some(PathBuf::from(&directory));
 
-                        let mut contents = Vec::new();
-                        let contents_str = match file.read_to_end(&mut contents) {
-                            Ok(_) => match String::from_utf8(contents.clone()) {
-                                Ok(c) => c,
-                                Err(_) => {
-                                    let (cow, _, had_errors) = GBK.decode(&contents);
-                                    if had_errors {
-                                        self.dyn_tis.borrow_mut().set_text(format!("文件 {} 不是文本文件，跳过检索", &relative_path).as_str());
-                                        continue; // 跳过此文件
-                                    }
-                                    cow.into_owned()
-                                }
-                            },
-                            Err(_) => {
-                                self.dyn_tis.borrow_mut().set_text(format!("文件 {} 不是文本文件，跳过检索", &relative_path).as_str());
-                                continue; // 跳过此文件
-                            }
-                        };
-                        search_in_file_contents(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), &contents_str, &relative_path).await;
-                    }
-                }
-            }
-        
-            Ok(())
-        });
-        feature.await
+        let rules = self.get_check_rules();
+        let rule_set = self.check_rule_set(rules.clone());
+        self.start_scan(directory, rule_set, rules, ScanTask::Check);
     }
-    
-    // 操作gz文件
-    async fn process_gz_file<R: AsyncReadExt + Unpin>(&self,  res: Arc<Mutex<Vec<MatchResult>>>, handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,  semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, mut reader: R,  gz_path: &Path, base_dir: Arc<&Path>) -> Result<(), Box<dyn Error>> {
-        let feature = Box::pin(async move {
-            
-            let mut buffer = Vec::new();
-            reader.read_to_end(&mut buffer).await?;
-            let cursor = Cursor::new(buffer);
-            let mut decoder = GzDecoder::new(cursor);
-            let mut decompressed_data = Vec::new();
-            match decoder.read_to_end(&mut decompressed_data) {
-                Ok(_) => (),
-                Err(e) => {
-                    self.dyn_tis.borrow_mut().set_text(format!("解压文件 {} 失败: {}", gz_path.to_string_lossy(), e).as_str());
-                    return Ok(());
-                }
-            }
-            // 假设.gz文件可能是.tar.gz
-            if gz_path.file_name().and_then(|name| name.to_str()).map_or(false, |name| name.ends_with(".tar.gz")) {
-                let cursor = Cursor::new(&decompressed_data);
-                return self.process_tar_bytes(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, gz_path, Arc::clone(&base_dir)).await;
-            }
-            let cursor = Cursor::new(&decompressed_data);
-            // 进一步检查解压后的文件类型
-            let archive = ZipArchive::new(cursor.clone());
-            if archive.is_ok() {
-                return self.process_zip_file(Arc::clone(&res), Arc::clone(&handles), Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, gz_path, Arc::clone(&base_dir)).await;
-            }
 
-            let cursor = Cursor::new(decompressed_data.clone());
-            let mut decoder = GzDecoder::new(cursor);
-            let mut nested_decompressed_data = Vec::new();
-            if decoder.read_to_end(&mut nested_decompressed_data).is_ok() {
-                let nested_cursor = Cursor::new(nested_decompressed_data);
-                return self.process_gz_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), nested_cursor, gz_path, Arc::clone(&base_dir)).await;
-            }
+    // 是否有后台任务在运行，运行中时提示
+    fn is_busy(&self) -> bool {
+        if self.running_scan.borrow().is_none() {
+            return false;
+        }
+        nwg::simple_message("提示", "请等待当前检测或脱敏导出结束后再试");
+        true
+    }
 
-            let contents_str = match String::from_utf8(decompressed_data.clone()) {
-                Ok(c) => c,
-                Err(_) => {
-                    let (cow, _, had_errors) = GBK.decode(&decompressed_data);
-                    if had_errors {
-                        self.dyn_tis.borrow_mut().set_text(format!("文件 {} 不是文本文件，跳过检索", gz_path.to_string_lossy()).as_str());
-                        return Ok(());
-                    }
-                    cow.into_owned()
+    // 在后台线程中扫描，事件经通道发回，界面线程收到通知后更新进度
+    fn start_scan(&self, directory: String, rule_set: RuleSet, rules: Vec<Rule>, task: ScanTask) {
+        let (sender, messages) = mpsc::channel();
+        let pending = Arc::new(AtomicBool::new(false));
+        let notice = self.scan_notice.sender();
+        let event_sender = sender.clone();
+        let event_pending = pending.clone();
+        let cancel = CancelToken::new();
+        let scanner = Scanner::new(rule_set, self.scan_options())
+            .with_cancel_token(cancel.clone())
+            .on_event(move |event| {
+                let _ = event_sender.send(ScanMessage::Event(event));
+                if !event_pending.swap(true, Ordering::AcqRel) {
+                    notice.notice();
                 }
-            };
-        
-            let relative_path = self.strip_base_dir(*base_dir, gz_path);
-            search_in_file_contents(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), &contents_str, &relative_path).await;
-        
-            Ok(())
+            });
+        let worker = thread::spawn(move || {
+            let report = scanner.scan_path(Path::new(&directory));
+            let _ = sender.send(ScanMessage::Done(report));
+            notice.notice();
+        });
+        self.check_button.set_text("停止检测");
+        self.progress_bar.set_pos(0);
+        // 清空上次结果的详情，扫描中即时展示的行点击后不展示原文
+        self.bind_copy_event(Vec::new(), Vec::new(), Vec::new());
+        *self.running_scan.borrow_mut() = Some(RunningScan {
+            messages,
+            pending,
+            cancel,
+            worker,
+            task,
+            rules,
+            start: Instant::now(),
+            progress: ScanProgress::default(),
+            findings: 0,
         });
-        feature.await
     }
-    
-    // 操作tar文件
-    async fn process_tar_bytes<R: AsyncReadExt + Unpin>(&self, res: Arc<Mutex<Vec<MatchResult>>>, handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>, semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, mut reader: R,  tar_path: &Path, base_dir: Arc<&Path>) -> Result<(), Box<dyn Error>> {
-        let feature = Box::pin(async move {
-            let mut buffer = [0; 512];
-        
-            loop {
-                if reader.read_exact(&mut buffer).await.is_err() {
-                    break;
-                }
-        
-                let file_name = match from_utf8(&buffer[0..100]) {
-                    Ok(name) => name.trim_matches(char::from(0)).to_string(),
-                    Err(_) => break,
-                };
-        
-                if file_name.is_empty() {
-                    break;
-                }
 
-                let size_str = match from_utf8(&buffer[124..136]) {
-                    Ok(size) => size.trim_matches(char::from(0)),
-                    Err(_) => break,
-                };
-        
-                let size = usize::from_str_radix(size_str, 8).unwrap_or(0);
-        
-                let mut contents = vec![0; size];
-                reader.read_exact(&mut contents).await?;
-        
-                let relative_path = format!("{}/{}", self.strip_base_dir(*base_dir, tar_path), file_name);
-        
-                if file_name.ends_with(".tar") {
-                    let cursor = Cursor::new(contents);
-                    self.process_tar_bytes(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                } else if file_name.ends_with(".gz") {
-                    let cursor = Cursor::new(contents);
-                    self.process_gz_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                } else if file_name.ends_with(".zip") {
-                    let cursor = Cursor::new(contents);
-                    self.process_zip_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                } else if file_name.ends_with(".war") {
-                    let cursor = Cursor::new(contents);
-                    self.process_war_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                } else if file_name.ends_with(".jar") {
-                    let cursor = Cursor::new(contents);
-                    self.process_war_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                } else if file_name.ends_with(".class") {
-                    let cursor = Cursor::new(contents);
-                    self.process_class_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), cursor, Path::new(&relative_path), Arc::clone(&base_dir)).await?;
-                } else {
-                    let contents_str = match String::from_utf8(contents.clone()) {
-                        Ok(c) => c,
-                        Err(_) => {
-                            let (cow, _, had_errors) = GBK.decode(&contents);
-                            if had_errors {
-                                self.dyn_tis.borrow_mut().set_text(format!("文件 {} 不是文本文件，跳过检索", tar_path.to_string_lossy()).as_str());
-                                continue;
-                            }
-                            cow.into_owned()
-                        }
-                    };
-                    search_in_file_contents(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), &contents_str,  &relative_path).await;
-                }
-        
-                let remainder = 512 - (size % 512);
-                if remainder < 512 {
-                    let mut skip = vec![0; remainder];
-                    reader.read_exact(&mut skip).await?;
-                }
+    // 停止检测：取消扫描，扫描线程退出后展示已得到的结果
+    fn stop_check(&self) {
+        if let Some(scan) = self.running_scan.borrow().as_ref() {
+            if let ScanTask::Redact(_) = scan.task {
+                nwg::simple_message("提示", "脱敏导出无法中途停止，请等待完成");
+                return;
             }
-        
-            Ok(())
-        });
-        feature.await
-    }
-    
-    // 操作war文件 jar也是
-    async fn process_war_file<R: AsyncReadExt + Unpin >(&self, res: Arc<Mutex<Vec<MatchResult>>>, handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,  semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, reader: R,  war_path: &Path, base_dir: Arc<&Path>) -> Result<(), Box<dyn Error>> {
-        // WAR 文件本质上是 ZIP 文件，所以我们可以调用 process_zip_file
-        self.process_zip_file(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), reader, war_path, Arc::clone(&base_dir)).await
+            scan.cancel.cancel();
+            self.search_tis.borrow_mut().set_text("正在停止...");
+        }
     }
 
-    // 操作class文件
-    async fn process_class_file<R: AsyncReadExt + Unpin>(&self, res: Arc<Mutex<Vec<MatchResult>>>, handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,  semaphore : Arc<Semaphore>,  regex_list: Arc<Vec<String>>, mut reader_origin: R,  class_path: &Path, base_dir: Arc<&Path>) -> Result<(), Box<dyn Error>> {
-        let relative_path = self.strip_base_dir(*base_dir, class_path);
-        let mut buffer = Vec::new();
-        if let Ok(_) = reader_origin.read_to_end(&mut buffer).await {
-            {
-                let mut command = Command::new("java");
-                    command.arg("-jar")
-                        .arg("./cfr.jar")
-                        .arg("--stdin") 
-                        .arg("class")
-                        .stdout(Stdio::piped())  // 将标准输出重定向到管道
-                        .stderr(Stdio::piped())  // 将标准错误重定向到管道
-                        .stdin(Stdio::piped());
-    
-                #[cfg(target_os = "windows")]
-                {
-                    command.creation_flags(0x08000000); // Windows 特定：创建隐藏窗口（仅在 Windows 平台上编译时有效）
-                }
-
-                let permit = semaphore.clone().acquire_owned().await.unwrap();
-                let handle = tokio::spawn(async move {
-                    // 运行命令并获取子进程的句柄
-                    match command.spawn() {
-                        Ok(mut c) => {
-                        // 获取子进程的标准输入句柄，并将 .class 文件的字节内容写入
-                        if let Some(mut stdin) = c.stdin.take() {
-                            // let mut buffer = Vec::new();
-                            // match reader.read_to_end(&mut buffer) {
-                            //     Ok(_) => {
-                                    match stdin.write_all(&buffer) {
-                                        Ok(_) => {
-                                            match stdin.flush() {
-                                                Ok(_) => {
-                                            
-                                                },
-                                                _ => {}
-                                            }
-                                        },
-                                        _ => {}
-                                    }
-                                    
-                                // },
-                                // _ => {}
-                            // }
-                            
-                        }
-                
-                        // 读取子进程的输出
-                        let mut output = Vec::new();
+    // 关闭窗口前取消后台检测，等待扫描线程结束正在运行的反编译进程
+    fn abort_check(&self) {
+        let scan = self.running_scan.borrow_mut().take();
+        if let Some(scan) = scan {
+            scan.cancel.cancel();
+            let _ = scan.worker.join();
+        }
+    }
 
-                        if let Some(mut stdout) = c.stdout.take() {
-                            if let Err(e) = stdout.read_to_end(&mut output) {
-                                eprintln!("Error reading stdout: {}", e);
-                            }
-                        }
-    
-                        // 处理输出
-                        if let Ok(output_status) = c.wait() {
-                            if output_status.success() {
-                                if !output.is_empty() {
-                                    let result = String::from_utf8_lossy(&output);
-                                    search_in_file_contents_sync(Arc::clone(&res), Arc::clone(&regex_list), &result, &relative_path).await;
-                                }
-                            } else {
-                                // 处理错误情况
-                                eprintln!("Command failed with status: {:?}", output_status);
-                            }
-                        }
-    
-                        drop(permit); // 释放信号量
-
-                        // if let Ok(output) = c.wait_with_output() {
-                        //     if output.status.success() {
-                        //         if !output.stdout.is_empty() {
-                        //             let result = String::from_utf8_lossy(&output.stdout);
-                        //             search_in_file_contents_sync(Arc::clone(&res), Arc::clone(&handles),  Arc::clone(&semaphore),Arc::clone(&regex_list), &result,  &relative_path).await;
-                        //         } else {
-                        //             // self.dyn_tis.set_text(
-                        //             //     format!(
-                        //             //         "反编译失败：{} {}",
-                        //             //         class_path.to_string_lossy(),
-                        //             //         base_dir.to_string_lossy()
-                        //             //     )
-                        //             //     .as_str(),
-                        //             // );
-                        //         }
-                        //     } else {
-                        //         // self.dyn_tis.set_text(
-                        //         //     format!(
-                        //         //         "反编译失败：{} {}",
-                        //         //         class_path.to_string_lossy(),
-                        //         //         String::from_utf8_lossy(&output.stderr)
-                        //         //     )
-                        //         //     .as_str(),
-                        //         // );
-                        //     }
-                        //     drop(permit); // 释放许可
-                        // }            
-                        
-                        },
-                        _ => {}
+    // 收到扫描线程的通知：更新进度并即时展示命中，扫描结束后展示结果
+    fn on_scan_notice(&self) {
+        let mut running = self.running_scan.borrow_mut();
+        let scan = match running.as_mut() {
+            Some(scan) => scan,
+            None => return,
+        };
+        scan.pending.store(false, Ordering::Release);
+        let mut done = None;
+        while let Ok(message) = scan.messages.try_recv() {
+            match message {
+                ScanMessage::Event(ScanEvent::Finding(result)) => {
+                    if !result.is_issue() {
+                        scan.findings += 1;
                     }
-            
-    
-                });//);
-                {
-                    let mut hs = handles.lock().await;
-                    hs.push(handle);
-                }
+                    // 检测时命中逐条追加到列表，结束后再按展示方式重新排列
+                    if let ScanTask::Check = scan.task {
+                        self.insert_live_row(&result, &scan.rules);
+                    }
+                },
+                ScanMessage::Event(ScanEvent::Progress(progress)) => scan.progress = progress,
+                ScanMessage::Event(_) => {}
+                message => done = Some(message),
             }
         }
+        let done = match done {
+            Some(done) => done,
+            None if scan.cancel.is_cancelled() => return,
+            None => {
+                let progress = scan.progress;
+                let task = if let ScanTask::Compliance = scan.task { "合规检查中..." } else { "搜索中..." };
+                self.progress_bar.set_pos((progress.bytes_done * PROGRESS_RANGE as u64 / progress.bytes_total.max(1)) as u32);
+                self.search_tis.borrow_mut().set_text(&format!(
+                    "{} 文件 {}/{}，{:.1}/{:.1} MB，已发现 {} 处",
+                    task,
+                    progress.files_done,
+                    progress.files_total,
+                    progress.bytes_done as f64 / 1048576.0,
+                    progress.bytes_total as f64 / 1048576.0,
+                    scan.findings
+                ));
+                return;
+            }
+        };
+        let scan = running.take().unwrap();
+        drop(running);
+        let _ = scan.worker.join();
+        self.check_button.set_text("开始检测");
+        self.progress_bar.set_marquee(false, 0);
+        self.progress_bar.remove_flags(nwg::ProgressBarFlags::MARQUEE);
+        self.progress_bar.set_pos(PROGRESS_RANGE);
+        let duration = scan.start.elapsed();
+
+        match (scan.task, done) {
+            (ScanTask::Redact(output_dir), ScanMessage::Redacted(result)) => {
+                match result {
+                    Ok(manifest) => {
+                        self.dyn_tis.borrow_mut().set_text(format!(
+                            "脱敏完成，共替换 {} 处，{} 个文件无法脱敏、未输出，清单见 {}",
+                            manifest.changes.len(),
+                            manifest.withheld.len(),
+                            output_dir.join(MANIFEST_FILE_NAME).to_string_lossy()
+                        ).as_str());
+                    },
+                    Err(e) => {
+                        nwg::simple_message("错误", &format!("脱敏导出失败: {}", e));
+                    }
+                }
+                self.search_tis.borrow_mut().set_text(format!("脱敏完成,耗时：{:?}",duration).as_str());
+            },
+            (ScanTask::Compliance, ScanMessage::Done(report)) => {
+                let cancelled = report.cancelled;
+                let all_res = self.take_findings(report);
+                self.show_compliance_results(&all_res, &scan.rules);
+                if cancelled {
+                    self.search_tis.borrow_mut().set_text(format!("合规检查已停止，仅统计停止前的结果,耗时：{:?}",duration).as_str());
+                } else {
+                    self.search_tis.borrow_mut().set_text(format!("合规检查完成,耗时：{:?}",duration).as_str());
+                }
+            },
+            (_, ScanMessage::Done(report)) => {
+                let cancelled = report.cancelled;
+                let summary = summary_line(&report.summary);
+                let all_res = self.take_findings(report);
+                self.list_view.clear();
+                self.show_check_results(all_res, &scan.rules);
+                if cancelled {
+                    self.search_tis.borrow_mut().set_text(format!("检测已停止，仅展示停止前的结果,耗时：{:?}，{}",duration, summary).as_str());
+                } else {
+                    self.search_tis.borrow_mut().set_text(format!("搜索完成,耗时：{:?}，{}",duration, summary).as_str());
+                }
+            },
+            _ => {}
+        }
+    }
 
-        
-        
-        
+    // 检测过程中即时展示一条命中，此时还不知道相邻行的命中，只遮盖匹配值本身
+    fn insert_live_row(&self, result: &MatchResult, rules: &[Rule]) {
+        let value = if self.mask_state.get() == MaskState::Masked {
+            mask_results(std::slice::from_ref(result), rules).pop().map(|masked| result_value(&masked)).unwrap_or_default()
+        } else {
+            result_value(result)
+        };
+        self.insert_result_row(&value, &result.location());
+    }
 
-        
-        Ok(())
+    // 展示检测结果
+    fn show_check_results(&self, all_res: Vec<MatchResult>, rules: &[Rule]) {
+        let masked = self.mask_state.get() == MaskState::Masked;
+        // 用于临时保存所有的完整文本和匹配文本
+        let mut matched_text_storage: Vec<String> = Vec::new();  // 新增
+        let mut full_text_storage: Vec<String> = Vec::new();
+        let mut file_name_storage: Vec<String> = Vec::new();
+        if self.result_state.get() == ResultState::Aggregate {
+            // 上下文中可能还有相邻行的其他命中值，脱敏时一并遮盖
+            let hits = HitIndex::new(&all_res);
+            // 聚合展示：相同规则的相同值只展示一行
+            for group in aggregate(&all_res, AGGREGATE_SAMPLE_LIMIT) {
+                let group = if masked { mask_aggregated(&group, rules, &hits) } else { group };
+                let location = format!(
+                    "{} 第 {} 行 等 {} 个文件 共 {} 处",
+                    group.first.file_name, group.first.line_number, group.file_counts.len(), group.total
+                );
+                self.insert_result_row(&group.matched_text, &location);
+
+                let file_counts: Vec<String> = group.file_counts.iter().map(|(f, c)| format!("{}: {} 处", f, c)).collect();
+                let samples: Vec<String> = group.samples.iter().map(|l| format!("{} 第 {} 行", l.file_name, l.line_number)).collect();
+                full_text_storage.push(format!(
+                    "{}\r\n\r\n各文件出现次数：\r\n{}\r\n\r\n位置示例：\r\n{}",
+                    group.origin_text, file_counts.join("\r\n"), samples.join("\r\n")
+                ));
+                file_name_storage.push(format!(
+                    "共 {} 处 | 首次 {} 第 {} 行 | 末次 {} 第 {} 行",
+                    group.total, group.first.file_name, group.first.line_number, group.last.file_name, group.last.line_number
+                ));
+                matched_text_storage.push(group.matched_text.clone());
+            }
+            // 匹配中止的行不参与聚合，逐条展示在最后
+            for result in all_res.iter().filter(|result| result.is_issue()) {
+                let result = if masked { mask_result(result, rules, &hits) } else { result.clone() };
+                let location = result.location();
+                self.insert_result_row(&result.issue_message().unwrap_or_default(), &location);
+                full_text_storage.push(result.origin_text.clone());
+                file_name_storage.push(location);
+                matched_text_storage.push(String::new());
+            }
+        } else {
+            // 默认对展示和复制的匹配值脱敏
+            let all_res = if masked { mask_results(&all_res, rules) } else { all_res };
+            for result in all_res {
+                let location = result.location();
+                self.insert_result_row(&result_value(&result), &location);
+
+                // 保存完整的 origin_text 和 matched_text 到临时存储中
+                full_text_storage.push(result.origin_text.clone());
+                file_name_storage.push(location);
+                matched_text_storage.push(result.matched_text.clone());  // 新增
+            }
+        }
+        // 将完整文本存储到 `ListView` 的 `userdata` 中
+        self.bind_copy_event(full_text_storage,file_name_storage, matched_text_storage);
     }
     
-    // 获取目录下所有文件
-    async fn get_all_file(&self, regex_list: Arc<Vec<String>>, path_dir: String) -> Result<Vec<MatchResult>, Box<dyn Error>> {
-        let res : Arc<Mutex<Vec<MatchResult>>> = Arc::new(Mutex::new(Vec::new()));
-        let semaphore : Arc<Semaphore> = Arc::new(Semaphore::new(8));
-        let handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>> = Arc::new(Mutex::new(Vec::new()));
-        let path = PathBuf::from(path_dir.clone());
-        let base_dir = Arc::new(path.as_path());
-
-        self.get_file_by_dir(Arc::clone(&res), Arc::clone(&handles), Arc::clone(&semaphore),Arc::clone(&regex_list), PathBuf::from(path_dir.clone()), Arc::clone(&base_dir)).await;
-
-        {
-            let mut hs = handles.lock().await;
-            let handles_vrc = hs.drain(..).collect::<Vec<_>>();
-            drop(hs);
-
-            for handle in handles_vrc {
-                handle.await.unwrap();
-            }
+    // 脱敏合规检查：统计每个文件中个人信息的脱敏比例
+    fn begin_compliance_check(&self) {
+        if self.is_busy() {
+            return;
         }
-       
+        self.list_view.clear();
+        let directory = self.path_input_text.borrow().text();
+        if directory.is_empty() {
+            self.path_input_text.borrow().set_text("请输入日志目录");
+            return;
+        }
+        if !self.check_enabled_rules() {
+            return;
+        }
+        self.search_tis.borrow_mut().set_text("合规检查中...");
 
-        let mut results = Vec::new();
-        {
-            let m = res.lock().await;
+        let rules = self.get_check_rules();
+        let compliance = compliance_rules(&rules);
+        if compliance.is_empty() {
+            self.dyn_tis.borrow_mut().set_text("启用的规则中没有配置脱敏策略的个人信息规则");
+            return;
+        }
 
-            for x in m.iter() {
-                if *self.line_state.borrow_mut() == LineState::Line3 {
-                    results.push(x.clone());
-                } else {
-                    let lines: Vec<&str> = x.origin_text.split('\n').collect();
-                    results.push( MatchResult {
-                        file_name: x.file_name.clone(),
-                        line_number: x.line_number.clone(),
-                        matched_text: x.matched_text.clone(),
-                        origin_text: lines[1].to_string(),
-                    });
-                }
-            }
+        let rule_set = self.check_rule_set(compliance);
+        self.start_scan(directory, rule_set, rules, ScanTask::Compliance);
+    }
+
+    // 展示合规检查结果，每个文件每类个人信息一行
+    fn show_compliance_results(&self, all_res: &[MatchResult], rules: &[Rule]) {
+        let mut matched_text_storage: Vec<String> = Vec::new();
+        let mut full_text_storage: Vec<String> = Vec::new();
+        let mut file_name_storage: Vec<String> = Vec::new();
+        for summary in summarize(all_res, rules) {
+            let percent = format!("{}%", summary.masked_percent());
+            self.insert_result_row(&percent, &summary.to_string());
+
+            // 详情中只展示未正确脱敏的位置，不展示值本身
+            full_text_storage.push(format!(
+                "未脱敏：\r\n{}\r\n\r\n部分脱敏：\r\n{}",
+                summary.unmasked.iter().map(|l| format!("第 {} 行", l)).collect::<Vec<_>>().join("\r\n"),
+                summary.partial.iter().map(|l| format!("第 {} 行", l)).collect::<Vec<_>>().join("\r\n")
+            ));
+            file_name_storage.push(summary.to_string());
+            matched_text_storage.push(percent);
         }
+        self.bind_copy_event(full_text_storage, file_name_storage, matched_text_storage);
+    }
 
+    // 用规则自带的示例测试规则库中的所有规则
+    fn begin_self_test(&self) {
+        let rules: Vec<(String, Rule)> = self.features.iter()
+            .flat_map(|feature| {
+                feature.list_box.collection().iter().enumerate()
+                    .map(|(i, pattern)| (format!("规则{} 第{}条", feature.id, i + 1), self.get_rule(pattern)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let examples: usize = rules.iter().map(|(_, rule)| rule.should_match.len() + rule.should_not_match.len()).sum();
+        let mut failures = Vec::new();
+        for (name, rule) in &rules {
+            failures.extend(test_rule(name, rule, &self.limits()));
+        }
+        if failures.is_empty() {
+            self.dyn_tis.borrow_mut().set_text(&format!("规则自测通过，共 {} 个示例", examples));
+        } else {
+            self.dyn_tis.borrow_mut().set_text(&format!("规则自测共 {} 个示例，{} 项失败", examples, failures.len()));
+            let content = failures.iter().map(|f| f.to_string()).collect::<Vec<_>>().join("\r\n");
+            nwg::simple_message("规则自测失败", &content);
+        }
+    }
 
-        Ok(results)
+    // 展示上次扫描的统计及跳过/失败的文件，完整报告复制到剪贴板
+    fn show_scan_report(&self) {
+        let last_scan = self.last_scan.borrow();
+        let report = match last_scan.as_ref() {
+            Some(report) => report,
+            None => {
+                nwg::simple_message("扫描报告", "还没有进行过检测");
+                return;
+            }
+        };
+        let mut content = scan_report_text(report, Some(REPORT_ISSUE_LIMIT));
+        if set_clipboard(formats::Unicode, scan_report_text(report, None)).is_ok() {
+            content = format!("{}\r\n\r\n完整报告已复制到剪贴板", content);
+        }
+        nwg::simple_message("扫描报告", &content);
     }
 
-    
-    // 检测按钮点击后
-    fn begin_check(&self) {
-        self.list_view.clear();
+    // 脱敏导出按钮点击后，将检索路径下的日志脱敏后写入选择的目录
+    fn begin_redact(&self) {
+        if self.is_busy() {
+            return;
+        }
         let directory = self.path_input_text.borrow().text();
         if directory.is_empty() {
             self.path_input_text.borrow().set_text("请输入日志目录");
             return;
         }
-        self.search_tis.borrow_mut().set_text("搜索中...");
-        let start = Instant::now();
-        
-
-        let regex_list: Arc<Vec<String>> = Arc::new(self.get_check_regex_list());
+        if !self.check_enabled_rules() {
+            return;
+        }
+        if !self.redact_dialog.run(Some(&self.window)) {
+            return;
+        }
+        let output_dir = match self.redact_dialog.get_selected_item() {
+            Ok(dir) => PathBuf::from(dir),
+            Err(_) => return,
+        };
+        self.search_tis.borrow_mut().set_text("脱敏中...");
+
+        // 脱敏导出没有进度事件，在后台线程中运行，进度条滚动表示进行中
+        let rule_set = self.check_rule_set(self.get_check_rules());
+        let limits = self.scan_options().limits;
+        let (sender, messages) = mpsc::channel();
+        let notice = self.scan_notice.sender();
+        let output = output_dir.clone();
+        let worker = thread::spawn(move || {
+            let result = redact_path(Path::new(&directory), &output, &rule_set, RedactStyle::Mask, limits).map_err(|e| e.to_string());
+            let _ = sender.send(ScanMessage::Redacted(result));
+            notice.notice();
+        });
+        self.progress_bar.add_flags(nwg::ProgressBarFlags::MARQUEE);
+        self.progress_bar.set_marquee(true, 30);
+        *self.running_scan.borrow_mut() = Some(RunningScan {
+            messages,
+            pending: Arc::new(AtomicBool::new(false)),
+            cancel: CancelToken::new(),
+            worker,
+            task: ScanTask::Redact(output_dir),
+            rules: Vec::new(),
+            start: Instant::now(),
+            progress: ScanProgress::default(),
+            findings: 0,
+        });
+    }
 
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        runtime.block_on(async {
-            let all_results = self.get_all_file(regex_list, directory).await;
-        
-            match all_results {
-                Ok(all_res) => {
-                    // 用于临时保存所有的完整文本和匹配文本
-                    let mut matched_text_storage: Vec<String> = Vec::new();  // 新增
-                    let mut full_text_storage: Vec<String> = Vec::new();
-                    let mut file_name_storage: Vec<String> = Vec::new();
-                    for result in all_res {
-                        let list_view_num = self.list_view.len();
-                        
-                        self.list_view.insert_item(nwg::InsertListViewItem {
-                            column_index: 0,
-                            text: Some(list_view_num.to_string()),
-                            index: Some(list_view_num as i32),
-                            image: None,
-                        });
-        
-                        self.list_view.insert_item(nwg::InsertListViewItem {
-                            column_index: 1,
-                            text: Some(result.matched_text.clone()),
-                            index: Some(list_view_num as i32),
-                            image: None,
-                        });
-
-
-                        self.list_view.insert_item(nwg::InsertListViewItem {
-                            column_index: 2,
-                            text: Some(format!("{} 第 {} 行", result.file_name, result.line_number)),
-                            index: Some(list_view_num as i32),
-                            image: None,
-                        });
-        
-                        // 保存完整的 origin_text 和 matched_text 到临时存储中
-                        full_text_storage.push(result.origin_text.clone());
-                        file_name_storage.push(format!("{} 第 {} 行", result.file_name, result.line_number));
-                        matched_text_storage.push(result.matched_text.clone());  // 新增
-                        
-                    }
-                    // 将完整文本存储到 `ListView` 的 `userdata` 中
-                    self.bind_copy_event(full_text_storage,file_name_storage, matched_text_storage);
+    // 按条目输入框中 a.war!/WEB-INF/lib/x.jar!/com/A.class 形式的路径取出单个条目，class 直接反编译展示，dex 等解析后展示，其余另存为文件。
+    // 路径与扫描结果一致，相对于上次扫描的路径，未扫描过时相对于日志目录输入框中的路径
+    fn extract_archive_entry(&self) {
+        let entry = self.entry_input_text.text();
+        if !entry.contains(ENTRY_SEPARATOR) {
+            nwg::simple_message("提示", "请在条目路径输入框中填写扫描结果里的条目路径，如 app.war!/WEB-INF/lib/x.jar!/com/A.class");
+            return;
+        }
+        let root = self.scan_root.borrow().clone().unwrap_or_else(|| PathBuf::from(self.path_input_text.borrow().text()));
+        let limits = self.scan_options().limits;
+        if entry.ends_with(".class") {
+            match decompile_entry(&root, &entry, Path::new(DEFAULT_CFR_JAR), &limits) {
+                Ok(source) => {
+                    self.origin_text.borrow_mut().set_text(&source.replace('\n', "\r\n"));
+                    self.dyn_tis.borrow_mut().set_text(format!("已反编译 {}", entry).as_str());
                 },
-                _ => { self.dyn_tis.borrow_mut().set_text("该目录或文件中有文件内容为非文本内容，筛查失败，请检查后再试") }
+                Err(e) => {
+                    nwg::simple_message("错误", &format!("反编译条目失败: {}", e));
+                }
             }
-            
-            let duration = start.elapsed();
-            self.search_tis.borrow_mut().set_text(format!("搜索完成,耗时：{:?}",duration).as_str());
+            return;
+        }
+        let bytes = match extract_entry(&root, &entry, &limits) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                nwg::simple_message("错误", &format!("提取条目失败: {}", e));
+                return;
+            }
+        };
+        // dex 及编译后的资源文件展示解析出的文本，行号与扫描结果一致
+        if let Some(file) = AndroidFile::sniff(&bytes) {
+            match file.decode(&bytes) {
+                Ok(text) => {
+                    self.origin_text.borrow_mut().set_text(&text.replace('\n', "\r\n"));
+                    self.dyn_tis.borrow_mut().set_text(format!("已解析 {}", entry).as_str());
+                },
+                Err(e) => {
+                    nwg::simple_message("错误", &format!("解析条目失败: {}", e));
+                }
+            }
+            return;
+        }
+        if !self.extract_dialog.run(Some(&self.window)) {
+            return;
+        }
+        let output = match self.extract_dialog.get_selected_item() {
+            Ok(output) => PathBuf::from(output),
+            Err(_) => return,
+        };
+        match fs::write(&output, bytes) {
+            Ok(()) => self.dyn_tis.borrow_mut().set_text(format!("已提取到 {}", output.to_string_lossy()).as_str()),
+            Err(e) => {
+                nwg::simple_message("错误", &format!("保存条目失败: {}", e));
+            }
+        }
+    }
+
+    // 向展示列表插入一行结果
+    fn insert_result_row(&self, matched_text: &str, location: &str) {
+        let list_view_num = self.list_view.len();
+
+        self.list_view.insert_item(nwg::InsertListViewItem {
+            column_index: 0,
+            text: Some(list_view_num.to_string()),
+            index: Some(list_view_num as i32),
+            image: None,
+        });
+
+        self.list_view.insert_item(nwg::InsertListViewItem {
+            column_index: 1,
+            text: Some(matched_text.to_string()),
+            index: Some(list_view_num as i32),
+            image: None,
+        });
+
+        self.list_view.insert_item(nwg::InsertListViewItem {
+            column_index: 2,
+            text: Some(location.to_string()),
+            index: Some(list_view_num as i32),
+            image: None,
         });
     }
-    
+
     fn bind_copy_event(&self, full_text_storage: Vec<String>,file_names: Vec<String>, matched_texts: Vec<String>) {
-        let copy_storage = Arc::new(full_text_storage);
-        let file_name_storage = Arc::new(file_names);
-        let matched_text_storage = Arc::new(matched_texts);  // 新增
+        let copy_storage = Rc::new(full_text_storage);
+        let file_name_storage = Rc::new(file_names);
+        let matched_text_storage = Rc::new(matched_texts);  // 新增
         // 解除之前的事件处理器
         if let Some(handler) = self.event_handler.borrow_mut().take() {
             nwg::unbind_event_handler(&handler);
@@ -889,19 +1089,17 @@ impl BasicApp {
         // 绑定 `ListView` 的激活事件来处理复制逻辑
         let list_view_handle = &self.list_view.handle;
         let window_handle = &self.window.handle;
-        let origin_text = Arc::clone(&self.origin_text);
-        let origin_file = Arc::clone(&self.origin_file);
-        let path_input_text = Arc::clone(&self.path_input_text);
+        let origin_text = Rc::clone(&self.origin_text);
+        let origin_file = Rc::clone(&self.origin_file);
+        let path_input_text = Rc::clone(&self.path_input_text);
         let new_handler = nwg::bind_event_handler(
             list_view_handle,  // 控件句柄
             window_handle,  // 父窗口句柄
             {
-                let copy_storage = Arc::clone(&copy_storage);
-                let file_name_storage = Arc::clone(&file_name_storage);
-                let matched_text_storage = Arc::clone(&matched_text_storage);  // 新增
-                let path_input_text = Arc::clone(&path_input_text);
-                // 正则表达式用于匹配 Unicode 转义字符
-                let re = Regex::new(r"\\u([0-9a-fA-F]{4})").unwrap();
+                let copy_storage = Rc::clone(&copy_storage);
+                let file_name_storage = Rc::clone(&file_name_storage);
+                let matched_text_storage = Rc::clone(&matched_text_storage);  // 新增
+                let path_input_text = Rc::clone(&path_input_text);
                 move |evt, evt_data, _handle| {
                     match evt {
                         nwg::Event::OnListViewClick => {
@@ -909,11 +1107,8 @@ impl BasicApp {
                             // 验证索引是否有效，防止崩溃
                             if index < copy_storage.len() {
                                 if let Some(full_text) = copy_storage.get(index) {
-                                    // 使用正则表达式替换 Unicode 转义字符
-                                    let unescaped_text = re.replace_all(full_text, |caps: &regex::Captures| {
-                                        let code_point = u16::from_str_radix(&caps[1], 16).unwrap();
-                                        char::from_u32(u32::from(code_point)).unwrap().to_string()
-                                    });
+                                    // 与匹配时一样解码 Unicode 转义、URL 编码及 HTML 实体后展示
+                                    let unescaped_text = normalize(full_text).text;
 
                                     // 将 \r\n 替换为 \n，标准化换行符，解决三行高亮异常的问题
                                     let unescaped_text = unescaped_text.replace("\r\n", "\n");
@@ -921,7 +1116,9 @@ impl BasicApp {
                                     origin_text.borrow_mut().set_text(&unescaped_text);
 
                                     // 新增代码：定位并选中匹配的文本
-                                    if let Some(matched_text) = matched_text_storage.get(index) {
+                                    // 匹配中止的行没有匹配值，不做高亮
+                                    if let Some(matched_text) = matched_text_storage.get(index).filter(|t| !t.is_empty()) {
+                                        let matched_text = &normalize(matched_text).text;
                                         let mut match_positions = Vec::new(); // 存储所有匹配的字符位置
                                     
                                         let mut search_start = 0;
@@ -1033,6 +1230,15 @@ impl BasicApp {
         let feature = &self.features[feature_id];
         let text = feature.input_text.text();
         if !text.is_empty() {
+            let name = format!("规则{} 第{}条", feature_id, feature.list_box.len() + 1);
+            let engine = match self.get_rule(&text).validate(&name, &self.limits()) {
+                Ok(engine) => engine,
+                Err(e) => {
+                    self.show_rule_errors("规则错误", &[e]);
+                    return;
+                }
+            };
+            self.dyn_tis.borrow_mut().set_text(&format!("{} 已添加，使用 {} 引擎", name, engine));
             feature.list_box.push(text);
             feature.input_text.set_text("");
         }
@@ -1101,16 +1307,6 @@ impl BasicApp {
         
         
     }
-
-
-
-    // 绝对路径变相对路径
-    fn strip_base_dir(&self, base_dir: &Path, full_path: &Path) -> String {
-        full_path.strip_prefix(base_dir)
-            .unwrap_or(full_path)
-            .to_string_lossy()
-            .to_string()
-    }
 }
     
 
@@ -1123,7 +1319,7 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
     use nwg::CheckBoxState;
 
     pub struct BasicAppUi {  // 定义 UI 管理结构体
-        inner: Arc<BasicApp>,  // 使用 Rc 封装 BasicApp，允许多处共享所有权
+        inner: Rc<BasicApp>,  // 使用 Rc 封装 BasicApp，允许多处共享所有权
         default_handler: RefCell<Option<nwg::EventHandler>>  // 事件处理器，用 RefCell 提供内部可变性
     }
 
@@ -1150,6 +1346,11 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                 .strict(true)
                 .build(&mut data.ico_capoo);
             data.window.set_icon(Some(&data.ico_capoo));
+
+            // 扫描线程通过该通知唤醒界面线程
+            nwg::Notice::builder()
+                .parent(&data.window)
+                .build(&mut data.scan_notice)?;
             
             // 初始化菜单和菜单项
             nwg::MenuItem::builder()
@@ -1197,12 +1398,82 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                 .parent(&data.window)
                 .build(&mut data.menu_switch_1_line)?;
 
+            nwg::MenuItem::builder()
+                .text("切换到聚合展示")
+                .parent(&data.window)
+                .build(&mut data.menu_switch_aggregate)?;
+
+            nwg::MenuItem::builder()
+                .text("切换到逐条展示")
+                .parent(&data.window)
+                .build(&mut data.menu_switch_each)?;
+
+            nwg::MenuItem::builder()
+                .text("切换到脱敏展示")
+                .parent(&data.window)
+                .build(&mut data.menu_switch_masked)?;
+
+            nwg::MenuItem::builder()
+                .text("切换到原始值展示")
+                .parent(&data.window)
+                .build(&mut data.menu_switch_raw)?;
+
+            nwg::MenuItem::builder()
+                .text("脱敏导出")
+                .parent(&data.window)
+                .build(&mut data.menu_redact)?;
+
+            nwg::MenuItem::builder()
+                .text("提取压缩包条目")
+                .parent(&data.window)
+                .build(&mut data.menu_extract_entry)?;
+
+            nwg::MenuItem::builder()
+                .text("脱敏合规检查")
+                .parent(&data.window)
+                .build(&mut data.menu_compliance)?;
+
+            nwg::MenuItem::builder()
+                .text("开启编码解码扫描")
+                .parent(&data.window)
+                .build(&mut data.menu_decode_on)?;
+
+            nwg::MenuItem::builder()
+                .text("关闭编码解码扫描")
+                .parent(&data.window)
+                .build(&mut data.menu_decode_off)?;
+
+            nwg::MenuItem::builder()
+                .text("规则自测")
+                .parent(&data.window)
+                .build(&mut data.menu_self_test)?;
+
+            nwg::MenuItem::builder()
+                .text("扫描报告")
+                .parent(&data.window)
+                .build(&mut data.menu_scan_report)?;
+
+            nwg::MenuItem::builder()
+                .text("开启单行超时保护")
+                .parent(&data.window)
+                .build(&mut data.menu_budget_on)?;
+
+            nwg::MenuItem::builder()
+                .text("关闭单行超时保护")
+                .parent(&data.window)
+                .build(&mut data.menu_budget_off)?;
+
             // 添加输入框和按钮
             nwg::TextInput::builder()
                 .parent(&data.window)
                 .text("此处粘贴日志目录")  // 初始文本为空
                 .build(&mut data.path_input_text.borrow_mut())?;
 
+            nwg::TextInput::builder()
+                .parent(&data.window)
+                .placeholder_text(Some("压缩包条目路径，用于菜单中的取出条目，如 app.war!/WEB-INF/lib/x.jar!/com/A.class"))
+                .build(&mut data.entry_input_text)?;
+
             nwg::Button::builder()
                 .text("选择文件/文件夹")
                 .parent(&data.window)
@@ -1225,7 +1496,7 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                 .size(20)          // 设置字体大小为 14，根据需要调整
                 .build(&mut data.rich_text_font)?;
 
-            data.origin_text = Arc::new(RefCell::new(nwg::RichTextBox::default()));
+            data.origin_text = Rc::new(RefCell::new(nwg::RichTextBox::default()));
             nwg::RichTextBox::builder()
                 .parent(&data.window)
                 .text("此处展示上下三行时，值所在行为中间那一行")  // 初始文本为空
@@ -1233,7 +1504,7 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                 .build(&mut data.origin_text.borrow_mut())?;
             data.origin_text.borrow_mut().set_background_color([155, 200, 200]);
             
-            data.origin_file = Arc::new(RefCell::new(nwg::TextInput::default()));
+            data.origin_file = Rc::new(RefCell::new(nwg::TextInput::default()));
             nwg::TextInput::builder()
                 .parent(&data.window)
                 .text("此处展示来源名")  // 初始文本为空
@@ -1259,12 +1530,27 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                 .build(&mut data.search_tis.borrow_mut())
                 .expect("动态文字展示出错");
 
+            nwg::ProgressBar::builder()
+                .parent(&data.window)
+                .range(0..PROGRESS_RANGE)
+                .build(&mut data.progress_bar)?;
+
             let _ = nwg::FileDialog::builder()
                 .title("Hello")
                 .action(nwg::FileDialogAction::Open)
                 .multiselect(true)
                 .build(&mut data.filedialog);
 
+            let _ = nwg::FileDialog::builder()
+                .title("选择脱敏输出目录")
+                .action(nwg::FileDialogAction::OpenDirectory)
+                .build(&mut data.redact_dialog);
+
+            let _ = nwg::FileDialog::builder()
+                .title("保存压缩包条目")
+                .action(nwg::FileDialogAction::Save)
+                .build(&mut data.extract_dialog);
+
             // 使用 InsertListViewColumn 来添加列
             data.list_view.set_headers_enabled(true);
 
@@ -1370,12 +1656,12 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
             data.event_handler = RefCell::new(None);
             // Event handling
             let ui = BasicAppUi {
-                inner: Arc::new(data),
+                inner: Rc::new(data),
                 default_handler: Default::default(),
             };
             
             // 事件绑定
-            let evt_ui = Arc::downgrade(&ui.inner);
+            let evt_ui = Rc::downgrade(&ui.inner);
             let handle_events = move |evt, _evt_data:nwg::EventData, handle| {
                 if let Some(ui) = evt_ui.upgrade() {
                     match evt {
@@ -1383,7 +1669,11 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                             if &handle == &ui.browse_button.handle {
                                 ui.open_file_dialog(&handle);
                             } else if &handle == &ui.check_button {
-                                ui.begin_check();
+                                if ui.running_scan.borrow().is_some() {
+                                    ui.stop_check();
+                                } else {
+                                    ui.begin_check();
+                                }
                             } else if &handle == &ui.clear_button {
                                 ui.clear_list_view();
                             } else {
@@ -1391,13 +1681,16 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                             }// 处理保存按钮点击
                         },
                         E::OnWindowClose => {
+                            // 先停止后台检测，反编译进程结束后才能删除 cfr.jar
+                            ui.abort_check();
                             // 清理临时文件
                             if let Err(e) = fs::remove_file("./cfr.jar") {
                                 eprintln!("未找到cfr临时文件: {}", e);
                             }
-                            std::process::exit(1);
+                            nwg::stop_thread_dispatch();
                         },
                         E::OnListViewRightClick => ui.match_copy(&handle),
+                        E::OnNotice if handle == ui.scan_notice.handle => ui.on_scan_notice(),
                         E::OnMenuItemSelected => {
                             if &handle == &ui.menu_about { // 关于按钮
                                 nwg::simple_message("关于&注意事项", text::ABOUT_TEXT);
@@ -1463,6 +1756,40 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                             } else if &handle == &ui.menu_switch_1_line {
                                 *ui.line_state.borrow_mut() = LineState::Line1;
                                 ui.dyn_tis.borrow_mut().set_text("切换到1行匹配值，下次搜索时生效");
+                            } else if &handle == &ui.menu_switch_aggregate {
+                                ui.result_state.set(ResultState::Aggregate);
+                                ui.dyn_tis.borrow_mut().set_text("切换到聚合展示，相同值只展示一行，下次搜索时生效");
+                            } else if &handle == &ui.menu_switch_each {
+                                ui.result_state.set(ResultState::Each);
+                                ui.dyn_tis.borrow_mut().set_text("切换到逐条展示，下次搜索时生效");
+                            } else if &handle == &ui.menu_switch_masked {
+                                ui.mask_state.set(MaskState::Masked);
+                                ui.dyn_tis.borrow_mut().set_text("切换到脱敏展示，下次搜索时生效");
+                            } else if &handle == &ui.menu_switch_raw {
+                                ui.mask_state.set(MaskState::Raw);
+                                ui.dyn_tis.borrow_mut().set_text("切换到原始值展示，匹配值将不脱敏直接展示和复制，下次搜索时生效");
+                            } else if &handle == &ui.menu_redact {
+                                ui.begin_redact();
+                            } else if handle == ui.menu_extract_entry {
+                                ui.extract_archive_entry();
+                            } else if &handle == &ui.menu_compliance {
+                                ui.begin_compliance_check();
+                            } else if &handle == &ui.menu_decode_on {
+                                ui.decode_state.set(DecodeState::On);
+                                ui.dyn_tis.borrow_mut().set_text("开启编码解码扫描，Base64/十六进制片段将解码后重新匹配，下次搜索时生效");
+                            } else if &handle == &ui.menu_decode_off {
+                                ui.decode_state.set(DecodeState::Off);
+                                ui.dyn_tis.borrow_mut().set_text("关闭编码解码扫描，下次搜索时生效");
+                            } else if &handle == &ui.menu_self_test {
+                                ui.begin_self_test();
+                            } else if &handle == &ui.menu_scan_report {
+                                ui.show_scan_report();
+                            } else if &handle == &ui.menu_budget_on {
+                                ui.budget_state.set(BudgetState::On);
+                                ui.dyn_tis.borrow_mut().set_text("开启单行超时保护，单行匹配超过1秒将中止并提示，匹配会变慢，下次搜索时生效");
+                            } else if &handle == &ui.menu_budget_off {
+                                ui.budget_state.set(BudgetState::Off);
+                                ui.dyn_tis.borrow_mut().set_text("关闭单行超时保护，下次搜索时生效");
                             }
                         },
                         E::OnListBoxSelect => ui.handle_list_box_select(&handle),
@@ -1520,6 +1847,8 @@ mod basic_app_ui {  // 定义一个模块，用于用户界面的管理
                 .child_item(nwg::GridLayoutItem::new(&ui.browse_button, col_num +1 , row_num + 2, 1, 1))
                 .child_item(nwg::GridLayoutItem::new(&ui.check_button, col_num , row_num + 3, 1, 1))
                 .child_item(nwg::GridLayoutItem::new(&ui.clear_button, col_num + 1 , row_num + 3, 1, 1))
+                .child_item(nwg::GridLayoutItem::new(&ui.entry_input_text, col_num, row_num + 4, 2, 1))
+                .child_item(nwg::GridLayoutItem::new(&ui.progress_bar, col_num, row_num + 5, 2, 1))
                 .child_item(nwg::GridLayoutItem::new(&ui.origin_text.borrow().handle, col_num + 2, 0, 2, 3))
                 .child_item(nwg::GridLayoutItem::new(&ui.origin_file.borrow().handle, col_num + 2, 3, 2, 1))
                 .child_item(nwg::GridLayoutItem::new(&ui.list_view, col_num + 2 , 4, 2, 14));
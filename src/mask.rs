@@ -0,0 +1,249 @@
+// 匹配值脱敏，避免导出的扫描结果本身泄露敏感信息
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::{AggregatedResult, Location};
+use crate::normalize::Decoded;
+use crate::rule::Rule;
+use crate::MatchResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mask {
+    pub keep_prefix: usize,  // 保留开头字符数
+    pub keep_suffix: usize,  // 保留结尾字符数
+}
+
+impl Mask {
+    // 手机号，按位数保留首尾，如 13*******00
+    pub const PHONE: Mask = Mask { keep_prefix: 3, keep_suffix: 4 };
+    // 身份证、密钥等 1101**********002X
+    pub const DEFAULT: Mask = Mask { keep_prefix: 4, keep_suffix: 4 };
+    // 整体遮盖，脱敏导出中的密钥等不保留任何字符
+    pub const FULL: Mask = Mask { keep_prefix: 0, keep_suffix: 0 };
+
+    // 根据值的形态自动选择脱敏方式
+    pub fn auto(value: &str) -> Mask {
+        if value.len() == 11 && value.starts_with('1') && value.bytes().all(|b| b.is_ascii_digit()) {
+            Mask::PHONE
+        } else {
+            Mask::DEFAULT
+        }
+    }
+
+    // 首尾各最多保留四分之一，保证至少有一半被遮盖
+    pub fn apply(&self, value: &str) -> String {
+        let chars: Vec<char> = value.chars().collect();
+        let len = chars.len();
+        let prefix = self.keep_prefix.min(len / 4);
+        let suffix = self.keep_suffix.min(len / 4);
+        chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| if i < prefix || i >= len - suffix { *c } else { '*' })
+            .collect()
+    }
+}
+
+// 邮箱只遮盖 @ 前的部分，如 zh******an@example.com，其他值整体按 mask 遮盖
+fn mask_value(value: &str, mask: Option<Mask>) -> String {
+    let mask = mask.unwrap_or_else(|| Mask::auto(value));
+    match value.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => format!("{}@{}", mask.apply(local), domain),
+        _ => mask.apply(value),
+    }
+}
+
+// 对匹配值脱敏，key=value 形式的匹配只遮盖值部分
+pub fn mask_match(matched: &str, mask: Option<Mask>) -> String {
+    if let Some(pos) = matched.find(['=', ':', '(', '>']) {
+        let (key, rest) = matched.split_at(pos);
+        let value_start = rest.find(|c: char| !"=:(>\"' ".contains(c)).unwrap_or(rest.len());
+        let value_end = rest.rfind(|c: char| !"\"' ,;)<".contains(c)).map_or(value_start, |i| i + 1);
+        if value_start < value_end {
+            let value = &rest[value_start..value_end];
+            return format!("{}{}{}{}", key, &rest[..value_start], mask_value(value, mask), &rest[value_end..]);
+        }
+    }
+    mask_value(matched, mask)
+}
+
+// 查找规则配置的脱敏方式
+fn rule_mask(rules: &[Rule], rule: &str) -> Option<Mask> {
+    rules.iter().find(|r| r.pattern == rule).and_then(|r| r.mask)
+}
+
+// 按文件及行号索引的命中，上下文中可能出现同一文件相邻行的其他命中值，需要一并遮盖
+pub struct HitIndex<'a> {
+    hits: HashMap<(&'a str, usize), Vec<&'a MatchResult>>,
+}
+
+impl<'a> HitIndex<'a> {
+    pub fn new(results: &'a [MatchResult]) -> HitIndex<'a> {
+        let mut hits: HashMap<(&str, usize), Vec<&MatchResult>> = HashMap::new();
+        for result in results.iter().filter(|result| !result.is_issue() && !result.matched_text.is_empty()) {
+            if let Ok(line) = result.line_number.parse() {
+                hits.entry((&result.file_name, line)).or_default().push(result);
+            }
+        }
+        HitIndex { hits }
+    }
+
+    // 遮盖 location 处命中的上下文（命中行及前后各一行）中出现的所有命中值，
+    // own 为该处自身的 (匹配值, 规则)，不在索引中时也一并遮盖
+    fn mask_context(&self, origin_text: &str, location: &Location, own: (&str, &str), rules: &[Rule]) -> String {
+        let line = location.line_number.parse::<usize>().ok();
+        let mut values: Vec<(&str, &str)> = line
+            .into_iter()
+            .flat_map(|line| line.saturating_sub(1)..=line + 1)
+            .filter_map(|n| self.hits.get(&(location.file_name.as_str(), n)))
+            .flatten()
+            .map(|hit| (hit.matched_text.as_str(), hit.rule.as_str()))
+            .chain(Some(own).filter(|(value, _)| !value.is_empty()))
+            .collect();
+        // 先替换较长的值，避免其中包含的较短命中值先被替换后长值无法匹配
+        values.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.cmp(b)));
+        values.dedup_by(|a, b| a.0 == b.0);
+        let mut masked = origin_text.to_string();
+        for (value, rule) in values {
+            masked = masked.replace(value, &mask_match(value, rule_mask(rules, rule)));
+        }
+        masked
+    }
+}
+
+// hits 为同一次扫描的所有结果，用于遮盖上下文中的其他命中值
+pub fn mask_result(result: &MatchResult, rules: &[Rule], hits: &HitIndex) -> MatchResult {
+    let masked = mask_match(&result.matched_text, rule_mask(rules, &result.rule));
+    MatchResult {
+        rule: result.rule.clone(),
+        matched_text: masked,
+        file_name: result.file_name.clone(),
+        line_number: result.line_number.clone(),
+        origin_text: hits.mask_context(&result.origin_text, &Location::of(result), (&result.matched_text, &result.rule), rules),
+        decoded: result.decoded.as_ref().map(|decoded| Decoded {
+            chain: decoded.chain.clone(),
+            text: mask_match(&decoded.text, rule_mask(rules, &result.rule)),
+        }),
+        issue: result.issue.clone(),
+    }
+}
+
+pub fn mask_results(results: &[MatchResult], rules: &[Rule]) -> Vec<MatchResult> {
+    let hits = HitIndex::new(results);
+    results.iter().map(|result| mask_result(result, rules, &hits)).collect()
+}
+
+// 聚合结果按原始值聚合后再脱敏，避免不同的值脱敏后被错误合并
+pub fn mask_aggregated(group: &AggregatedResult, rules: &[Rule], hits: &HitIndex) -> AggregatedResult {
+    AggregatedResult {
+        value: mask_match(&group.value, rule_mask(rules, &group.rule)),
+        origin_text: hits.mask_context(&group.origin_text, &group.first, (&group.matched_text, &group.rule), rules),
+        matched_text: mask_match(&group.matched_text, rule_mask(rules, &group.rule)),
+        ..group.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(rule: &str, matched_text: &str, line_number: &str, origin_text: &str) -> MatchResult {
+        MatchResult {
+            rule: rule.to_string(),
+            matched_text: matched_text.to_string(),
+            file_name: "app.log".to_string(),
+            line_number: line_number.to_string(),
+            origin_text: origin_text.to_string(),
+            decoded: None,
+            issue: None,
+        }
+    }
+
+    #[test]
+    fn apply_keeps_prefix_and_suffix() {
+        assert_eq!(Mask::auto("13800138000"), Mask::PHONE);
+        assert_eq!(Mask::auto("23800138000"), Mask::DEFAULT);
+        assert_eq!(Mask::PHONE.apply("13800138000"), "13*******00");
+        assert_eq!(Mask::DEFAULT.apply("11010519491231002X"), "1101**********002X");
+        assert_eq!(Mask::DEFAULT.apply("sk-0123456789abcdefghij"), "sk-0***************ghij");
+        // 多字节字符按字符遮盖
+        assert_eq!(Mask::DEFAULT.apply("张三李四王五赵六钱七"), "张三******钱七");
+    }
+
+    #[test]
+    fn apply_masks_at_least_half_of_every_value() {
+        for len in 0..40 {
+            let value = "x".repeat(len);
+            let masked = Mask::DEFAULT.apply(&value).chars().filter(|c| *c == '*').count();
+            assert!(masked * 2 >= len, "{} 位只遮盖了 {} 位", len, masked);
+        }
+        assert_eq!(Mask::DEFAULT.apply("abcdefghi"), "ab*****hi");
+        assert_eq!(Mask::DEFAULT.apply("abcdefgh"), "ab****gh");
+        assert_eq!(Mask::DEFAULT.apply("abc"), "***");
+        assert_eq!(Mask::DEFAULT.apply(""), "");
+        assert_eq!(Mask { keep_prefix: 0, keep_suffix: 0 }.apply("secret"), "******");
+    }
+
+    #[test]
+    fn mask_match_only_masks_values() {
+        assert_eq!(mask_match("password=\"Secret123\",", None), "password=\"Se*****23\",");
+        assert_eq!(mask_match("phone: 13800138000", None), "phone: 13*******00");
+        // 没有值时整体遮盖
+        assert_eq!(mask_match("key=''", None), "k****'");
+        assert_eq!(mask_match("13800138000", Some(Mask::DEFAULT)), "13*******00");
+    }
+
+    #[test]
+    fn mask_match_masks_email_local_part() {
+        assert_eq!(mask_match("bob@example.com", None), "***@example.com");
+        assert_eq!(mask_match("zhangsan@example.com", None), "zh****an@example.com");
+        assert_eq!(mask_match("mail: zhangsan@example.com", None), "mail: zh****an@example.com");
+        assert_eq!(mask_match("@example.com", None), "@ex******com");
+    }
+
+    #[test]
+    fn mask_results_covers_neighbouring_hits() {
+        let rules = vec![Rule::new("phone"), Rule { mask: Some(Mask { keep_prefix: 1, keep_suffix: 1 }), ..Rule::new("key") }];
+        let context = "a 13800138000\r\nkey=abcdef 13800138000\r\nb 13900139000";
+        let results = vec![
+            result("phone", "13800138000", "1", "\r\na 13800138000\r\nkey=abcdef 13800138000"),
+            result("key", "key=abcdef", "2", context),
+            result("phone", "13900139000", "3", "key=abcdef 13800138000\r\nb 13900139000\r\n"),
+        ];
+        let masked = mask_results(&results, &rules);
+        assert_eq!(masked[1].matched_text, "key=a****f");
+        assert_eq!(masked[1].origin_text, "a 13*******00\r\nkey=a****f 13*******00\r\nb 13*******00");
+        assert_eq!(masked[0].origin_text, "\r\na 13*******00\r\nkey=a****f 13*******00");
+    }
+
+    #[test]
+    fn mask_result_masks_decoded_value() {
+        let mut hit = result("phone", "MTM4MDAxMzgwMDA=", "1", "v=MTM4MDAxMzgwMDA=");
+        hit.decoded = Some(Decoded { chain: vec!["base64".to_string()], text: "13800138000".to_string() });
+        let masked = mask_results(&[hit], &[]);
+        assert_eq!(masked[0].decoded.as_ref().unwrap().text, "13*******00");
+        assert_eq!(masked[0].decoded.as_ref().unwrap().chain, vec!["base64"]);
+        assert!(!masked[0].origin_text.contains("MTM4MDAxMzgwMDA="));
+    }
+
+    #[test]
+    fn issues_are_not_hits() {
+        let mut issue = result("phone", "", "1", "13800138000");
+        issue.issue = Some("超出匹配次数限制".to_string());
+        let masked = mask_results(&[issue], &[]);
+        assert_eq!(masked[0].origin_text, "13800138000");
+        assert_eq!(masked[0].issue.as_deref(), Some("超出匹配次数限制"));
+    }
+
+    #[test]
+    fn mask_aggregated_masks_value_and_context() {
+        let results = vec![result("phone", "13800138000", "1", "\r\ncall 13800138000\r\n")];
+        let groups = crate::aggregate::aggregate(&results, 3);
+        let masked = mask_aggregated(&groups[0], &[], &HitIndex::new(&results));
+        assert_eq!(masked.value, "13*******00");
+        assert_eq!(masked.matched_text, "13*******00");
+        assert_eq!(masked.origin_text, "\r\ncall 13*******00\r\n");
+        assert_eq!(masked.total, 1);
+    }
+}
@@ -0,0 +1,236 @@
+// 匹配前的文本归一化：解码 Java/JSON Unicode 转义、URL 百分号编码及 HTML 实体
+use std::fmt;
+
+// 解码后的匹配值及经过的解码步骤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    pub chain: Vec<String>,  // 解码步骤，如 ["unicode"]
+    pub text: String,        // 解码后的匹配值
+}
+
+impl fmt::Display for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} → \"{}\"", self.chain.join(" → "), self.text)
+    }
+}
+
+const DECODERS: [&str; 3] = ["unicode", "url", "html"];
+
+#[derive(Debug, Clone)]
+pub struct Normalized {
+    pub text: String,
+    offsets: Vec<usize>,        // 归一化文本每个字节对应的原文字节位置，末尾多一个原文长度
+    sources: Vec<Option<u8>>,   // 归一化文本每个字节由哪种解码产生，下标对应 DECODERS
+}
+
+impl Normalized {
+    pub fn is_changed(&self) -> bool {
+        self.sources.iter().any(Option::is_some)
+    }
+
+    // 范围内实际生效的解码方式
+    pub fn decoders_in(&self, start: usize, end: usize) -> Vec<String> {
+        let mut decoders = Vec::new();
+        for source in self.sources[start..end].iter().flatten() {
+            let decoder = DECODERS[*source as usize].to_string();
+            if !decoders.contains(&decoder) {
+                decoders.push(decoder);
+            }
+        }
+        decoders
+    }
+
+    // 将归一化文本中的范围映射回原文
+    pub fn original_range(&self, start: usize, end: usize) -> (usize, usize) {
+        let original_start = self.offsets[start];
+        if end <= start {
+            return (original_start, original_start);
+        }
+        // 结尾落在转义序列解码出的字符上时，延伸到该序列结束
+        let last = self.offsets[end - 1];
+        let original_end = self.offsets[end..].iter().copied().find(|offset| *offset > last).unwrap_or(last);
+        (original_start, original_end)
+    }
+}
+
+fn hex_value(bytes: &[u8]) -> Option<u32> {
+    if !bytes.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    let s = std::str::from_utf8(bytes).ok()?;
+    u32::from_str_radix(s, 16).ok()
+}
+
+// 解析 \uXXXX，返回 (字符, 消耗的字节数)，支持代理对
+fn unicode_escape(bytes: &[u8]) -> Option<(char, usize)> {
+    if bytes.len() < 6 || bytes[0] != b'\\' || bytes[1] != b'u' {
+        return None;
+    }
+    let unit = hex_value(&bytes[2..6])?;
+    if (0xD800..0xDC00).contains(&unit) {
+        if bytes.len() < 12 || bytes[6] != b'\\' || bytes[7] != b'u' {
+            return None;
+        }
+        let low = hex_value(&bytes[8..12]).filter(|low| (0xDC00..0xE000).contains(low))?;
+        let code_point = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+        return char::from_u32(code_point).map(|c| (c, 12));
+    }
+    char::from_u32(unit).map(|c| (c, 6))
+}
+
+// 解析连续的 %XX，要求能组成合法的 UTF-8，返回 (文本, 消耗的字节数)
+fn percent_escape(bytes: &[u8]) -> Option<(String, usize)> {
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i + 3 <= bytes.len() && bytes[i] == b'%' {
+        match hex_value(&bytes[i + 1..i + 3]) {
+            Some(b) => decoded.push(b as u8),
+            None => break,
+        }
+        i += 3;
+    }
+    if decoded.is_empty() {
+        return None;
+    }
+    // 只取最长的合法 UTF-8 前缀
+    let valid = match std::str::from_utf8(&decoded) {
+        Ok(_) => decoded.len(),
+        Err(e) => e.valid_up_to(),
+    };
+    if valid == 0 {
+        return None;
+    }
+    let text = String::from_utf8(decoded[..valid].to_vec()).ok()?;
+    Some((text, valid * 3))
+}
+
+// 解析 HTML 实体，返回 (字符, 消耗的字节数)
+fn html_entity(bytes: &[u8]) -> Option<(char, usize)> {
+    if bytes.first() != Some(&b'&') {
+        return None;
+    }
+    let end = bytes.iter().take(12).position(|b| *b == b';')?;
+    let entity = std::str::from_utf8(&bytes[1..end]).ok()?;
+    let c = if let Some(hex) = entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+        char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+    } else if let Some(dec) = entity.strip_prefix('#') {
+        char::from_u32(dec.parse().ok()?)?
+    } else {
+        match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => ' ',
+            _ => return None,
+        }
+    };
+    Some((c, end + 1))
+}
+
+pub fn normalize(line: &str) -> Normalized {
+    let bytes = line.as_bytes();
+    // 绝大多数行不含转义，直接返回
+    if !bytes.iter().any(|b| matches!(b, b'\\' | b'%' | b'&')) {
+        return Normalized {
+            text: line.to_string(),
+            offsets: (0..=line.len()).collect(),
+            sources: vec![None; line.len()],
+        };
+    }
+
+    let mut text = String::with_capacity(line.len());
+    let mut offsets = Vec::with_capacity(line.len() + 1);
+    let mut sources = Vec::with_capacity(line.len());
+    let mut push = |c: char, origin: usize, source: Option<u8>| {
+        text.push(c);
+        offsets.resize(offsets.len() + c.len_utf8(), origin);
+        sources.resize(sources.len() + c.len_utf8(), source);
+    };
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let consumed = match bytes[i] {
+            b'\\' => unicode_escape(&bytes[i..]).map(|(c, n)| {
+                push(c, i, Some(0));
+                n
+            }),
+            b'%' => percent_escape(&bytes[i..]).map(|(s, n)| {
+                // 每个字符对应到其第一个 %XX 的位置
+                for (j, c) in s.char_indices() {
+                    push(c, i + j * 3, Some(1));
+                }
+                n
+            }),
+            b'&' => html_entity(&bytes[i..]).map(|(c, n)| {
+                push(c, i, Some(2));
+                n
+            }),
+            _ => None,
+        };
+        match consumed {
+            Some(n) => i += n,
+            None => {
+                // 按字符拷贝，保证多字节字符完整
+                let c = line[i..].chars().next().unwrap();
+                push(c, i, None);
+                i += c.len_utf8();
+            }
+        }
+    }
+    offsets.push(line.len());
+    Normalized { text, offsets, sources }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_line_is_unchanged() {
+        let normalized = normalize("password=abc 中文");
+        assert_eq!(normalized.text, "password=abc 中文");
+        assert!(!normalized.is_changed());
+        assert_eq!(normalized.original_range(9, 12), (9, 12));
+    }
+
+    #[test]
+    fn decodes_unicode_escapes() {
+        let line = r"key=\u0070\u0077\u0064 \ud83d\ude00 \uZZZZ \ud83d";
+        let normalized = normalize(line);
+        assert_eq!(normalized.text, r"key=pwd 😀 \uZZZZ \ud83d");
+        assert_eq!(normalized.decoders_in(4, 7), vec!["unicode"]);
+        // 解码出的字符映射回整个转义序列
+        assert_eq!(normalized.original_range(4, 7), (4, 22));
+        assert_eq!(&line[23..35], r"\ud83d\ude00");
+        assert_eq!(normalized.original_range(8, 12), (23, 35));
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        let normalized = normalize("q=%E4%B8%AD%E6%96%87&x=%2x%ZZ%41%FF");
+        assert_eq!(normalized.text, "q=中文&x=%2x%ZZA%FF");
+        assert_eq!(normalized.decoders_in(2, 8), vec!["url"]);
+        assert_eq!(normalized.original_range(2, 5), (2, 11));
+        assert_eq!(normalized.original_range(5, 8), (11, 20));
+    }
+
+    #[test]
+    fn decodes_html_entities() {
+        let normalized = normalize("a&amp;b &#65;&#x42; &unknown; &lt;");
+        assert_eq!(normalized.text, "a&b AB &unknown; <");
+        assert_eq!(normalized.decoders_in(0, 3), vec!["html"]);
+        assert_eq!(normalized.original_range(0, 3), (0, 7));
+        assert!(normalized.decoders_in(7, 16).is_empty());
+    }
+
+    #[test]
+    fn reports_each_decoder_once() {
+        let normalized = normalize(r"\u0061%62&#99;d");
+        assert_eq!(normalized.text, "abcd");
+        assert_eq!(normalized.decoders_in(0, 4), vec!["unicode", "url", "html"]);
+        assert_eq!(normalized.original_range(0, 4), (0, 15));
+        assert_eq!(normalized.original_range(2, 2), (9, 9));
+    }
+}
@@ -0,0 +1,421 @@
+// 规则及配置文件结构
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::archive::ENTRY_SEPARATOR;
+use crate::compliance::MaskPolicy;
+use crate::engine::{Engine, Limits, Matcher};
+use crate::mask::Mask;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<Mask>,  // 脱敏方式，不填则按匹配值自动选择
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<MaskPolicy>,  // 脱敏合规检查策略，不填则内置规则使用默认策略
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,  // 只在路径匹配这些通配符的文件中生效，不填则所有文件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,  // 路径匹配这些通配符的文件不使用该规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<PatternVariant>,  // 特定文件类型使用的替代正则，按顺序取第一个匹配的
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub should_match: Vec<String>,  // 应当命中的示例，用于规则自测
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub should_not_match: Vec<String>,  // 不应命中的示例
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_limit: Option<u32>,  // 匹配次数限制，不填则使用扫描的默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth_limit: Option<u32>,  // 回溯深度限制，不填则使用扫描的默认值；PCRE2 JIT 不支持该限制，填写后该规则解释执行
+}
+
+// 规则在特定文件中使用的替代正则，如代码文件中要求值被引号包裹
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternVariant {
+    pub files: Vec<String>,  // 路径通配符，如 "*.class"
+    pub pattern: String,
+}
+
+// 发布包规则库第一条密钥匹配，class/java 代码中强制要求值被引号包裹
+const PACKAGE_KEY_RULE: &str = r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\s?[\"\']?(=|:)+\s?[\"\']?[a-zA-Z0-9\@\.]+[\"\']?"#;
+const PACKAGE_KEY_QUOTED: &str = r#"((P|p)((A|a)(S|s)(S|s))?(W|w)((O|o)(R|r))?(D|d)|(K|k)(E|e)(Y|y)|(E|e)(N|n)(C|c)(R|r)(Y|y)(P|p)(T|t)|(S|s)(E|e)(C|c)(R|r)(E|e)(T|t)|(A|a)(U|u)(T|t)(H|h)((O|o)(R|r)(I|i)(Z|z)(A|a)(T|t)(I|i)(O|o)(N|n))?)\s?[\"\']?(=|:)+\s?[\"\']+[a-zA-Z0-9\@\.]+[\"\']+"#;
+
+// 内置规则默认携带的替代正则：(规则, 文件通配符, 替代正则)
+const BUILTIN_VARIANTS: &[(&str, &[&str], &str)] = &[
+    (PACKAGE_KEY_RULE, &["*.class", "*.java"], PACKAGE_KEY_QUOTED),
+];
+
+impl Rule {
+    pub fn new(pattern: &str) -> Rule {
+        Rule {
+            pattern: pattern.to_string(),
+            mask: None,
+            policy: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            variants: Vec::new(),
+            should_match: Vec::new(),
+            should_not_match: Vec::new(),
+            match_limit: None,
+            depth_limit: None,
+        }
+    }
+
+    // 带上内置默认配置的规则，配置文件中直接写成字符串的规则按此读取
+    pub fn builtin(pattern: &str) -> Rule {
+        let mut rule = Rule::new(pattern);
+        for (builtin, files, variant) in BUILTIN_VARIANTS {
+            if *builtin == pattern {
+                rule.variants.push(PatternVariant {
+                    files: files.iter().map(|f| f.to_string()).collect(),
+                    pattern: variant.to_string(),
+                });
+            }
+        }
+        rule
+    }
+
+    // 没有额外配置的规则在配置文件中直接写成字符串
+    fn is_plain(&self) -> bool {
+        *self == Rule::builtin(&self.pattern)
+    }
+
+    // 规则是否作用于该文件
+    pub fn applies_to(&self, file_name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| matches_path(glob, file_name)))
+            && !self.exclude.iter().any(|glob| matches_path(glob, file_name))
+    }
+
+    // 该文件实际使用的正则
+    pub fn pattern_for(&self, file_name: &str) -> &str {
+        self.variants
+            .iter()
+            .find(|variant| variant.files.iter().any(|glob| matches_path(glob, file_name)))
+            .map_or(&self.pattern, |variant| &variant.pattern)
+    }
+
+    // 规则自身配置的限制覆盖扫描的默认限制
+    pub fn limits(&self, defaults: &Limits) -> Limits {
+        Limits {
+            match_limit: self.match_limit.unwrap_or(defaults.match_limit),
+            depth_limit: self.depth_limit.or(defaults.depth_limit),
+            line_budget: defaults.line_budget,
+        }
+    }
+
+    // 检查规则中的所有正则（包括替代正则及脱敏策略）能否按扫描时的限制编译，返回规则主正则使用的引擎
+    pub fn validate(&self, name: &str, defaults: &Limits) -> Result<Engine, RuleError> {
+        let compiled = CompiledRule::new(name, self, defaults);
+        let engine = compiled.check()?;
+        if let Some(policy) = &self.policy {
+            compile_pattern(&format!("{} 脱敏策略", name), &policy.masked_pattern, &compiled.limits)?;
+        }
+        Ok(engine)
+    }
+}
+
+// 编译后的规则，扫描开始前编译一次，各文件、各工作线程共用
+pub struct CompiledRule {
+    pub rule: Rule,
+    limits: Limits,
+    matcher: Result<Matcher, RuleError>,
+    variants: Vec<Result<Matcher, RuleError>>,  // 与 rule.variants 一一对应
+}
+
+impl CompiledRule {
+    pub fn new(name: &str, rule: &Rule, defaults: &Limits) -> CompiledRule {
+        let limits = rule.limits(defaults);
+        CompiledRule {
+            rule: rule.clone(),
+            matcher: compile_pattern(name, &rule.pattern, &limits),
+            variants: rule
+                .variants
+                .iter()
+                .map(|variant| compile_pattern(&format!("{} 替代正则", name), &variant.pattern, &limits))
+                .collect(),
+            limits,
+        }
+    }
+
+    // 该文件实际使用的正则及其编译结果，与 Rule::pattern_for 的选择一致
+    pub fn matcher_for(&self, file_name: &str) -> (&str, Result<&Matcher, &RuleError>) {
+        for (variant, matcher) in self.rule.variants.iter().zip(&self.variants) {
+            if variant.files.iter().any(|glob| matches_path(glob, file_name)) {
+                return (&variant.pattern, matcher.as_ref());
+            }
+        }
+        (&self.rule.pattern, self.matcher.as_ref())
+    }
+
+    // 规则主正则的编译结果
+    pub fn matcher(&self) -> Result<&Matcher, &RuleError> {
+        self.matcher.as_ref()
+    }
+
+    // 主正则及替代正则都能编译时返回主正则使用的引擎
+    pub fn check(&self) -> Result<Engine, RuleError> {
+        let engine = self.matcher.as_ref().map_err(Clone::clone)?.engine();
+        match self.variants.iter().find_map(|variant| variant.as_ref().err()) {
+            Some(e) => Err(e.clone()),
+            None => Ok(engine),
+        }
+    }
+}
+
+// 编译后的规则集，按 RuleSet 中的限制编译
+pub struct CompiledRuleSet {
+    pub rules: Vec<CompiledRule>,
+    pub decode_depth: usize,
+}
+
+// 规则编译错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError {
+    pub name: String,           // 规则名称，如 "规则0 第3条"
+    pub pattern: String,        // 编译失败的正则
+    pub offset: Option<usize>,  // 出错位置在正则中的字节偏移
+    pub message: String,        // 正则引擎给出的错误信息
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "{} 编译失败，偏移 {}：{}，正则：{}", self.name, offset, self.message, self.pattern),
+            None => write!(f, "{} 编译失败：{}，正则：{}", self.name, self.message, self.pattern),
+        }
+    }
+}
+
+impl Error for RuleError {}
+
+// 编译正则，失败时给出规则名称、出错偏移及错误信息
+pub fn compile_pattern(name: &str, pattern: &str, limits: &Limits) -> Result<Matcher, RuleError> {
+    Matcher::new(pattern, limits).map_err(|e| RuleError {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+        offset: e.offset,
+        message: e.message,
+    })
+}
+
+// 路径通配符匹配，不区分大小写：* 不跨目录，** 可跨目录，? 匹配单个字符。
+// 不含 / 的通配符只匹配文件名，以 . 开头且不含通配符的视为扩展名，如 ".yml"；压缩包条目路径中的 !/ 按目录处理
+pub fn matches_path(glob: &str, file_name: &str) -> bool {
+    let glob = glob.replace('\\', "/").to_lowercase();
+    let path = file_name.replace(ENTRY_SEPARATOR, "/").replace('\\', "/").to_lowercase();
+    if glob.contains('/') {
+        return wildcard(glob.trim_start_matches('/').as_bytes(), path.trim_start_matches('/').as_bytes());
+    }
+    let name = path.rsplit('/').next().unwrap_or("");
+    if glob.starts_with('.') && !glob.contains(['*', '?']) {
+        return name.ends_with(&glob);
+    }
+    wildcard(glob.as_bytes(), name.as_bytes())
+}
+
+fn wildcard(glob: &[u8], path: &[u8]) -> bool {
+    match glob.first() {
+        None => path.is_empty(),
+        Some(b'*') if glob.get(1) == Some(&b'*') => {
+            let rest = &glob[2..];
+            // **/ 可以匹配零层目录
+            if rest.first() == Some(&b'/') && wildcard(&rest[1..], path) {
+                return true;
+            }
+            (0..=path.len()).any(|i| wildcard(rest, &path[i..]))
+        }
+        Some(b'*') => {
+            for i in 0..=path.len() {
+                if wildcard(&glob[1..], &path[i..]) {
+                    return true;
+                }
+                if i < path.len() && path[i] == b'/' {
+                    break;
+                }
+            }
+            false
+        }
+        Some(b'?') => !path.is_empty() && path[0] != b'/' && wildcard(&glob[1..], &path[1..]),
+        Some(c) => path.first() == Some(c) && wildcard(&glob[1..], &path[1..]),
+    }
+}
+
+// 一次扫描使用的规则及扫描选项
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+    pub decode_depth: usize,  // Base64/十六进制片段解码后重新匹配的最大层数，0 表示不解码
+    pub limits: Limits,       // 默认的匹配限制，规则可单独覆盖
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<Rule>) -> RuleSet {
+        RuleSet {
+            rules,
+            decode_depth: 0,
+            limits: Limits::default(),
+        }
+    }
+
+    // 扫描前检查所有规则，有错误时不应开始扫描
+    pub fn validate(&self) -> Vec<RuleError> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter_map(|(i, rule)| rule.validate(&format!("第{}条", i + 1), &self.limits).err())
+            .collect()
+    }
+
+    // 编译所有规则，无法编译的规则在匹配时跳过
+    pub fn compile(&self) -> CompiledRuleSet {
+        CompiledRuleSet {
+            rules: self
+                .rules
+                .iter()
+                .enumerate()
+                .map(|(i, rule)| CompiledRule::new(&format!("第{}条", i + 1), rule, &self.limits))
+                .collect(),
+            decode_depth: self.decode_depth,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RuleConfig {
+    pub name: String,
+    pub enabled: bool,
+    #[serde(deserialize_with = "deserialize_rules", serialize_with = "serialize_rules")]
+    pub patterns: Vec<Rule>,
+}
+
+impl RuleConfig {
+    pub fn validate(&self, limits: &Limits) -> Vec<RuleError> {
+        self.patterns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, rule)| rule.validate(&format!("{} 第{}条", self.name, i + 1), limits).err())
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct YamlConfig {
+    pub rules: Vec<RuleConfig>,
+    // 加密的 zip 条目依次尝试的密码，如供应商发布包的固定密码
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zip_passwords: Vec<String>,
+}
+
+impl YamlConfig {
+    pub fn validate(&self, limits: &Limits) -> Vec<RuleError> {
+        self.rules.iter().flat_map(|group| group.validate(limits)).collect()
+    }
+}
+
+// 兼容旧配置文件：patterns 中的元素既可以是正则字符串，也可以是完整的规则
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleEntry {
+    Pattern(String),
+    Rule(Box<Rule>),
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RuleEntryRef<'a> {
+    Pattern(&'a str),
+    Rule(&'a Rule),
+}
+
+fn deserialize_rules<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Rule>, D::Error> {
+    let entries = Vec::<RuleEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            RuleEntry::Pattern(pattern) => Rule::builtin(&pattern),
+            RuleEntry::Rule(rule) => *rule,
+        })
+        .collect())
+}
+
+fn serialize_rules<S: Serializer>(rules: &[Rule], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(rules.iter().map(|rule| {
+        if rule.is_plain() {
+            RuleEntryRef::Pattern(&rule.pattern)
+        } else {
+            RuleEntryRef::Rule(rule)
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_single_and_double_star() {
+        assert!(wildcard(b"*.yml", b"app.yml"));
+        assert!(wildcard(b"*", b""));
+        assert!(!wildcard(b"*.yml", b"conf/app.yml"));
+        assert!(wildcard(b"conf/*/app.yml", b"conf/prod/app.yml"));
+        assert!(!wildcard(b"conf/*/app.yml", b"conf/a/b/app.yml"));
+        assert!(wildcard(b"conf/**/app.yml", b"conf/a/b/app.yml"));
+        assert!(wildcard(b"conf/**/app.yml", b"conf/app.yml"));
+        assert!(wildcard(b"**", b"a/b/c"));
+        assert!(wildcard(b"a?c", b"abc"));
+        assert!(!wildcard(b"a?c", b"a/c"));
+        assert!(!wildcard(b"a?c", b"ac"));
+    }
+
+    #[test]
+    fn matches_path_by_name_extension_and_directory() {
+        assert!(matches_path(".yml", "conf/app.yml"));
+        assert!(matches_path(".YML", "CONF\\APP.yml"));
+        assert!(!matches_path(".yml", "conf/app.yaml"));
+        assert!(matches_path("*.class", "app.war!/WEB-INF/classes/com/A.class"));
+        // 含 / 的通配符从路径开头匹配
+        assert!(matches_path("**/WEB-INF/**/*.class", "app.war!/WEB-INF/classes/com/A.class"));
+        assert!(!matches_path("WEB-INF/**/*.class", "app.war!/WEB-INF/classes/com/A.class"));
+        assert!(!matches_path("**/WEB-INF/*.class", "app.war!/WEB-INF/classes/com/A.class"));
+        assert!(matches_path("/logs/*.log", "logs/app.log"));
+        assert!(matches_path("**/test/**", "src/test/java/A.java"));
+        assert!(!matches_path("app.?og", "app.log/x"));
+    }
+
+    #[test]
+    fn applies_to_include_and_exclude() {
+        let rule = Rule {
+            include: vec!["*.properties".to_string(), "*.yml".to_string()],
+            exclude: vec!["**/test/**".to_string()],
+            ..Rule::new("password")
+        };
+        assert!(rule.applies_to("conf/app.yml"));
+        assert!(rule.applies_to("app.jar!/application.properties"));
+        assert!(!rule.applies_to("src/test/app.yml"));
+        assert!(!rule.applies_to("app.log"));
+        assert!(Rule::new("password").applies_to("anything.bin"));
+    }
+
+    #[test]
+    fn builtin_variants_apply_to_code_files() {
+        let rule = Rule::builtin(PACKAGE_KEY_RULE);
+        assert_eq!(rule.pattern_for("app.jar!/com/A.class"), PACKAGE_KEY_QUOTED);
+        assert_eq!(rule.pattern_for("src/A.java"), PACKAGE_KEY_QUOTED);
+        assert_eq!(rule.pattern_for("application.yml"), PACKAGE_KEY_RULE);
+        assert!(rule.is_plain());
+        assert!(!Rule { match_limit: Some(10), ..rule }.is_plain());
+    }
+
+    #[test]
+    fn rule_limits_override_defaults() {
+        let defaults = Limits::default();
+        let rule = Rule { match_limit: Some(10), ..Rule::new("a") };
+        let limits = rule.limits(&defaults);
+        assert_eq!(limits.match_limit, 10);
+        assert_eq!(limits.depth_limit, defaults.depth_limit);
+        assert!(Rule::new("(").validate("坏规则", &defaults).is_err());
+    }
+}
@@ -0,0 +1,1217 @@
+// 扫描器：遍历目录与压缩包、反编译 class 文件，并按规则匹配其中的文本，不依赖界面。
+// 遍历在调用线程中进行，匹配与反编译交给线程池，不需要 tokio；启用 async 特性后另外提供异步接口
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use encoding_rs::GBK;
+use sevenz_rust::{BlockDecoder, SevenZMethod};
+use zip::read::ZipArchive;
+use zip::result::ZipError;
+
+use crate::archive::{self, Compression, Format, TarReader};
+use crate::rule::{CompiledRuleSet, RuleSet};
+use crate::{search_timed, MatchResult};
+
+// 线程池中同时进行的匹配及反编译任务数
+pub const DEFAULT_CONCURRENCY: usize = 8;
+pub const DEFAULT_CFR_JAR: &str = "./cfr.jar";
+// 嵌套压缩包在内存中缓存的上限，更大的转存到临时文件
+pub const DEFAULT_NESTED_BUFFER_LIMIT: u64 = 64 * 1024 * 1024;
+
+// 展开压缩包的默认限制
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+pub const DEFAULT_MAX_ENTRY_SIZE: u64 = 512 * 1024 * 1024;
+pub const DEFAULT_MAX_TOTAL_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+pub const DEFAULT_MAX_RATIO: u64 = 200;
+// 解压后小于该大小时不检查压缩比，重复内容多的小文件压缩比本来就高
+const RATIO_CHECK_MIN_SIZE: u64 = 1024 * 1024;
+// 超出展开限制时结果中的规则名
+pub const LIMIT_RULE: &str = "解压限制";
+
+// 等待反编译进程时检查取消的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+type ScanResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+// 扫描事件回调，在遍历线程及线程池中调用
+type EventHandler = Arc<dyn Fn(ScanEvent) + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    pub concurrency: usize,
+    pub cfr_jar: Option<PathBuf>,  // CFR 反编译器路径，不填则跳过 class 文件
+    pub nested_buffer_limit: u64,
+    pub limits: ArchiveLimits,
+    pub zip_passwords: Vec<String>,  // 加密的 zip 条目依次尝试的密码，支持 ZipCrypto 与 AES
+}
+
+impl Default for ScanOptions {
+    fn default() -> ScanOptions {
+        ScanOptions {
+            concurrency: DEFAULT_CONCURRENCY,
+            cfr_jar: Some(PathBuf::from(DEFAULT_CFR_JAR)),
+            nested_buffer_limit: DEFAULT_NESTED_BUFFER_LIMIT,
+            limits: ArchiveLimits::default(),
+            zip_passwords: Vec::new(),
+        }
+    }
+}
+
+// 展开压缩包的限制，防止解压炸弹或自我嵌套的压缩包耗尽内存，超出时记录为一条结果
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveLimits {
+    pub max_depth: usize,     // 压缩包嵌套层数
+    pub max_entry_size: u64,  // 单个条目或压缩文件解压后的大小
+    pub max_total_size: u64,  // 目录中的一个文件总共解压出的大小
+    pub max_ratio: u64,       // 解压后与压缩后的大小之比
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> ArchiveLimits {
+        ArchiveLimits {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_entry_size: DEFAULT_MAX_ENTRY_SIZE,
+            max_total_size: DEFAULT_MAX_TOTAL_SIZE,
+            max_ratio: DEFAULT_MAX_RATIO,
+        }
+    }
+}
+
+// 超出的展开限制，扫描、脱敏导出及提取条目共用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LimitExceeded {
+    Depth,
+    EntrySize,
+    TotalSize,
+    Ratio,
+}
+
+impl ArchiveLimits {
+    // 本次解压最多读取的字节数，读出更多即超出限制；expanded 为所在文件已解压出的字节数，compressed_size 为 0 表示压缩后大小未知
+    pub(crate) fn read_limit(&self, expanded: u64, compressed_size: u64) -> u64 {
+        let limit = self.max_entry_size.min(self.max_total_size.saturating_sub(expanded));
+        if compressed_size == 0 {
+            return limit;
+        }
+        limit.min(compressed_size.saturating_mul(self.max_ratio).max(RATIO_CHECK_MIN_SIZE))
+    }
+
+    // 检查解压出的 size 字节，expanded 为此前已计入的解压总量；会继续展开的内容不计入总量，但同样不能超出剩余的额度
+    pub(crate) fn check(&self, size: u64, expanded: u64, compressed_size: u64) -> Result<(), LimitExceeded> {
+        if size > self.max_entry_size {
+            Err(LimitExceeded::EntrySize)
+        } else if expanded.saturating_add(size) > self.max_total_size {
+            Err(LimitExceeded::TotalSize)
+        } else if compressed_size > 0 && size > compressed_size.saturating_mul(self.max_ratio).max(RATIO_CHECK_MIN_SIZE) {
+            Err(LimitExceeded::Ratio)
+        } else {
+            Ok(())
+        }
+    }
+
+    // 超出限制的原因，由调用方补充后续如何处理
+    pub(crate) fn describe(&self, exceeded: LimitExceeded) -> String {
+        match exceeded {
+            LimitExceeded::Depth => format!("压缩包嵌套超过 {} 层", self.max_depth),
+            LimitExceeded::EntrySize => format!("解压后超过 {} 字节", self.max_entry_size),
+            LimitExceeded::TotalSize => format!("所在文件解压总量超过 {} 字节", self.max_total_size),
+            LimitExceeded::Ratio => format!("压缩比超过 {}:1，疑似解压炸弹", self.max_ratio),
+        }
+    }
+}
+
+// 压缩包及压缩文件的内容会继续展开，其中的字节在展开时才计入解压总量
+pub(crate) fn expands(format: Option<Format>) -> bool {
+    matches!(format, Some(Format::Zip | Format::SevenZ | Format::Tar | Format::Compressed(_)))
+}
+
+// 跳过或处理失败的文件
+#[derive(Debug, Clone)]
+pub struct FileIssue {
+    pub file_name: String,
+    pub reason: String,
+}
+
+// 扫描统计
+#[derive(Debug, Clone, Default)]
+pub struct ScanSummary {
+    pub files_visited: usize,       // 目录中的文件及压缩包内的条目
+    pub bytes_scanned: u64,         // 参与匹配的文本字节数，含反编译出的源码
+    pub archives_opened: usize,     // 成功打开的 zip/gz/tar，含嵌套的
+    pub classes_decompiled: usize,
+    pub limits_exceeded: usize,     // 超出解压限制、未继续展开的次数
+    pub rule_counts: BTreeMap<String, usize>,   // 每条规则的命中数，不含匹配中止的行
+    pub file_counts: BTreeMap<String, usize>,   // 每个文件的命中数
+    pub rule_time: BTreeMap<String, Duration>,  // 每条规则的匹配耗时，为各线程耗时之和
+    pub duration: Duration,         // 整个扫描的耗时
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    pub summary: ScanSummary,
+    pub findings: Vec<MatchResult>,
+    pub skipped: Vec<FileIssue>,  // 不是文本、未配置反编译器等原因跳过的文件
+    pub errors: Vec<FileIssue>,   // 读取、解压或反编译失败的文件
+    pub cancelled: bool,          // 扫描被取消，结果只包含取消前完成的部分
+}
+
+impl ScanReport {
+    fn failed(file_name: &str, reason: String) -> ScanReport {
+        ScanReport {
+            errors: vec![FileIssue {
+                file_name: file_name.to_string(),
+                reason,
+            }],
+            ..ScanReport::default()
+        }
+    }
+}
+
+// 扫描进度，按目录中的文件统计，不含压缩包内的条目
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanProgress {
+    pub files_done: usize,  // 已遍历完的文件数，其中的内容可能仍在匹配
+    pub files_total: usize, // 开始扫描前统计的文件总数，扫描内存中的内容时为 1
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+// 取消扫描的令牌，可克隆后交给其他线程，取消后停止遍历、丢弃排队的任务并结束正在运行的反编译进程
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+// 扫描过程中实时发出的事件，界面可据此显示进度及已有结果
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    FileStarted { file_name: String },                  // 开始匹配或反编译
+    FileFinished { file_name: String, findings: usize },
+    Finding(MatchResult),
+    Skipped(FileIssue),
+    Error(FileIssue),
+    Progress(ScanProgress),
+}
+
+// 交给线程池执行的任务
+enum Job {
+    Search { contents: String, file_name: String },
+    Decompile { bytes: Vec<u8>, file_name: String, cfr_jar: PathBuf },
+}
+
+#[derive(Clone)]
+pub struct Scanner {
+    rule_set: Arc<RuleSet>,
+    compiled: Arc<CompiledRuleSet>,  // 创建扫描器时编译一次，各次扫描及工作线程共用
+    options: ScanOptions,
+    on_event: Option<EventHandler>,
+    cancel: CancelToken,
+}
+
+impl Scanner {
+    pub fn new(rule_set: RuleSet, options: ScanOptions) -> Scanner {
+        Scanner {
+            compiled: Arc::new(rule_set.compile()),
+            rule_set: Arc::new(rule_set),
+            options,
+            on_event: None,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    // 设置事件回调，需要通道时可在回调中转发给 mpsc::Sender
+    pub fn on_event(mut self, handler: impl Fn(ScanEvent) + Send + Sync + 'static) -> Scanner {
+        self.on_event = Some(Arc::new(handler));
+        self
+    }
+
+    // 设置取消令牌，不设置时扫描无法取消
+    pub fn with_cancel_token(mut self, cancel: CancelToken) -> Scanner {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+
+    pub fn rule_set(&self) -> &RuleSet {
+        &self.rule_set
+    }
+
+    pub fn options(&self) -> &ScanOptions {
+        &self.options
+    }
+
+    // 扫描文件或目录，结果中的文件名为相对 path 的路径，path 是文件时为文件名
+    pub fn scan_path(&self, path: &Path) -> ScanReport {
+        let (files_total, bytes_total) = count_files(path);
+        self.run(path, files_total, bytes_total, |walk| {
+            if path.is_file() {
+                walk.walk_file(path);
+            } else {
+                walk.walk_dir(path);
+            }
+        })
+    }
+
+    // 扫描内存中的文件内容，file_name 用于判断文件类型及作为结果中的文件名
+    pub fn scan_bytes(&self, bytes: &[u8], file_name: &str) -> ScanReport {
+        self.run(Path::new(""), 1, bytes.len() as u64, |walk| {
+            walk.expanded.set(0);
+            if let Err(e) = walk.walk_bytes(bytes.to_vec(), file_name) {
+                walk.error(file_name, e.to_string());
+            }
+            walk.file_done(bytes.len() as u64);
+        })
+    }
+
+    pub fn scan_reader<R: Read>(&self, mut reader: R, file_name: &str) -> ScanReport {
+        let mut bytes = Vec::new();
+        if let Err(e) = reader.read_to_end(&mut bytes) {
+            return ScanReport::failed(file_name, format!("读取失败: {}", e));
+        }
+        self.scan_bytes(&bytes, file_name)
+    }
+
+    // 启动线程池，在当前线程中遍历，遍历结束且任务全部完成后返回
+    fn run(&self, base_dir: &Path, files_total: usize, bytes_total: u64, walk: impl FnOnce(&Walk)) -> ScanReport {
+        let start = Instant::now();
+        let report = Mutex::new(ScanReport::default());
+        let workers = self.options.concurrency.max(1);
+        // 有界队列，遍历速度超过匹配速度时阻塞，避免读入的文件内容堆积
+        let (sender, receiver) = mpsc::sync_channel::<Job>(workers * 2);
+        let receiver = Mutex::new(receiver);
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let job = match receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => break,  // 队列已关闭且没有剩余任务
+                    };
+                    // 取消后继续取出任务但不执行，遍历线程不会阻塞在队列上
+                    if !self.cancel.is_cancelled() {
+                        self.execute(job, &report);
+                    }
+                });
+            }
+            let state = Walk {
+                scanner: self,
+                base_dir: base_dir.to_path_buf(),
+                sender,
+                report: &report,
+                progress: Cell::new(ScanProgress {
+                    files_total,
+                    bytes_total,
+                    ..ScanProgress::default()
+                }),
+                depth: Cell::new(0),
+                expanded: Cell::new(0),
+            };
+            self.emit(ScanEvent::Progress(state.progress.get()));
+            walk(&state);
+            drop(state);  // 关闭队列，线程池处理完剩余任务后退出
+        });
+        let mut report = report.into_inner().unwrap();
+        report.cancelled = self.cancel.is_cancelled();
+        for finding in report.findings.iter().filter(|finding| !finding.is_issue()) {
+            *report.summary.rule_counts.entry(finding.rule.clone()).or_default() += 1;
+            *report.summary.file_counts.entry(finding.file_name.clone()).or_default() += 1;
+        }
+        report.summary.duration = start.elapsed();
+        report
+    }
+
+    fn emit(&self, event: ScanEvent) {
+        if let Some(handler) = &self.on_event {
+            handler(event);
+        }
+    }
+
+    fn execute(&self, job: Job, report: &Mutex<ScanReport>) {
+        let file_name = match &job {
+            Job::Search { file_name, .. } | Job::Decompile { file_name, .. } => file_name.clone(),
+        };
+        self.emit(ScanEvent::FileStarted { file_name: file_name.clone() });
+        let contents = match job {
+            Job::Search { contents, .. } => contents,
+            Job::Decompile { bytes, cfr_jar, .. } => match decompile(&cfr_jar, &bytes, &self.cancel) {
+                Ok(source) => {
+                    report.lock().unwrap().summary.classes_decompiled += 1;
+                    source
+                }
+                // 取消时进程被结束，不算反编译失败
+                Err(_) if self.cancel.is_cancelled() => String::new(),
+                Err(e) => {
+                    let issue = FileIssue {
+                        file_name: file_name.clone(),
+                        reason: format!("反编译失败: {}", e),
+                    };
+                    report.lock().unwrap().errors.push(issue.clone());
+                    self.emit(ScanEvent::Error(issue));
+                    String::new()
+                }
+            },
+        };
+        let (findings, timings) = if contents.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            search_timed(&self.compiled, &contents, &file_name)
+        };
+        let count = findings.len();
+        if self.on_event.is_some() {
+            for finding in findings.iter() {
+                self.emit(ScanEvent::Finding(finding.clone()));
+            }
+        }
+        {
+            let mut report = report.lock().unwrap();
+            report.summary.bytes_scanned += contents.len() as u64;
+            for (rule, elapsed) in timings {
+                *report.summary.rule_time.entry(rule).or_default() += elapsed;
+            }
+            report.findings.extend(findings);
+        }
+        self.emit(ScanEvent::FileFinished { file_name, findings: count });
+    }
+}
+
+// 异步接口，在 tokio 的阻塞线程中运行同步扫描
+#[cfg(feature = "async")]
+impl Scanner {
+    pub async fn scan_path_async(&self, path: PathBuf) -> ScanReport {
+        let scanner = self.clone();
+        let file_name = path.to_string_lossy().to_string();
+        tokio::task::spawn_blocking(move || scanner.scan_path(&path))
+            .await
+            .unwrap_or_else(|e| ScanReport::failed(&file_name, format!("扫描任务异常退出: {}", e)))
+    }
+
+    pub async fn scan_bytes_async(&self, bytes: Vec<u8>, file_name: String) -> ScanReport {
+        let scanner = self.clone();
+        let name = file_name.clone();
+        tokio::task::spawn_blocking(move || scanner.scan_bytes(&bytes, &name))
+            .await
+            .unwrap_or_else(|e| ScanReport::failed(&file_name, format!("扫描任务异常退出: {}", e)))
+    }
+
+    pub async fn scan_reader_async<R: tokio::io::AsyncRead + Unpin>(&self, mut reader: R, file_name: &str) -> ScanReport {
+        use tokio::io::AsyncReadExt;
+
+        let mut bytes = Vec::new();
+        if let Err(e) = reader.read_to_end(&mut bytes).await {
+            return ScanReport::failed(file_name, format!("读取失败: {}", e));
+        }
+        self.scan_bytes_async(bytes, file_name.to_string()).await
+    }
+}
+
+// 一次扫描的遍历状态
+struct Walk<'a> {
+    scanner: &'a Scanner,
+    base_dir: PathBuf,
+    sender: SyncSender<Job>,
+    report: &'a Mutex<ScanReport>,
+    progress: Cell<ScanProgress>,
+    depth: Cell<usize>,   // 当前所在的压缩包嵌套层数
+    expanded: Cell<u64>,  // 当前文件已解压出的字节数
+}
+
+impl Walk<'_> {
+    fn skip(&self, file_name: &str, reason: &str) {
+        let issue = FileIssue {
+            file_name: file_name.to_string(),
+            reason: reason.to_string(),
+        };
+        self.report.lock().unwrap().skipped.push(issue.clone());
+        self.scanner.emit(ScanEvent::Skipped(issue));
+    }
+
+    fn error(&self, file_name: &str, reason: String) {
+        let issue = FileIssue {
+            file_name: file_name.to_string(),
+            reason,
+        };
+        self.report.lock().unwrap().errors.push(issue.clone());
+        self.scanner.emit(ScanEvent::Error(issue));
+    }
+
+    // 超出展开限制时记录为一条单独的结果，该文件不再展开
+    fn limit_exceeded(&self, file_name: &str, reason: String) {
+        let finding = MatchResult {
+            rule: LIMIT_RULE.to_string(),
+            matched_text: String::new(),
+            file_name: file_name.to_string(),
+            line_number: String::new(),
+            origin_text: reason.clone(),
+            decoded: None,
+            issue: Some(reason),
+        };
+        {
+            let mut report = self.report.lock().unwrap();
+            report.summary.limits_exceeded += 1;
+            report.findings.push(finding.clone());
+        }
+        self.scanner.emit(ScanEvent::Finding(finding));
+    }
+
+    // 进入一层压缩包，超过嵌套层数时不再展开
+    fn enter_archive(&self, file_name: &str, walk: impl FnOnce() -> ScanResult) -> ScanResult {
+        let depth = self.depth.get();
+        let limits = &self.scanner.options.limits;
+        if depth >= limits.max_depth {
+            self.limit_exceeded(file_name, format!("{}，不再展开", limits.describe(LimitExceeded::Depth)));
+            return Ok(());
+        }
+        self.depth.set(depth + 1);
+        let result = walk();
+        self.depth.set(depth);
+        result
+    }
+
+    // 本次解压最多读取的字节数，读出更多即超出限制；compressed_size 为 0 表示压缩后大小未知
+    fn expand_limit(&self, compressed_size: u64) -> u64 {
+        self.scanner.options.limits.read_limit(self.expanded.get(), compressed_size)
+    }
+
+    // 解压总量超出后，同一文件中其余的条目不再解压
+    fn expand_exhausted(&self) -> bool {
+        self.expanded.get() > self.scanner.options.limits.max_total_size
+    }
+
+    // 检查解压出的字节数，超出单个大小、解压总量或压缩比限制时报告并返回 false；
+    // 内容为 format 格式，会继续展开时不计入解压总量，展开出的内容再计入，每个解压出的字节只计一次
+    fn within_limits(&self, file_name: &str, size: u64, compressed_size: u64, format: Option<Format>) -> bool {
+        let limits = &self.scanner.options.limits;
+        let result = limits.check(size, self.expanded.get(), compressed_size);
+        // 超出解压总量时同样计入，同一文件中其余的条目不再解压
+        if !expands(format) || result == Err(LimitExceeded::TotalSize) {
+            self.expanded.set(self.expanded.get().saturating_add(size));
+        }
+        let Err(exceeded) = result else {
+            return true;
+        };
+        let action = if exceeded == LimitExceeded::TotalSize { "其余内容不再解压" } else { "不再读取" };
+        self.limit_exceeded(file_name, format!("{}，{}", limits.describe(exceeded), action));
+        false
+    }
+
+    // 一个文件遍历完成，更新进度
+    fn file_done(&self, bytes: u64) {
+        let mut progress = self.progress.get();
+        progress.files_done += 1;
+        progress.bytes_done += bytes;
+        // 统计后新增的文件可能使已完成数超过总数
+        progress.files_total = progress.files_total.max(progress.files_done);
+        progress.bytes_total = progress.bytes_total.max(progress.bytes_done);
+        self.progress.set(progress);
+        self.scanner.emit(ScanEvent::Progress(progress));
+    }
+
+    fn submit(&self, job: Job) {
+        if self.cancelled() {
+            return;
+        }
+        // 线程池在遍历结束前不会退出，发送不会失败
+        let _ = self.sender.send(job);
+    }
+
+    fn cancelled(&self) -> bool {
+        self.scanner.cancel.is_cancelled()
+    }
+
+    fn count(&self, update: impl FnOnce(&mut ScanSummary)) {
+        update(&mut self.report.lock().unwrap().summary);
+    }
+
+    // 绝对路径变相对路径，扫描单个文件时使用文件名
+    fn relative_path(&self, path: &Path) -> String {
+        match path.strip_prefix(&self.base_dir) {
+            Ok(relative) if relative.as_os_str().is_empty() => {
+                path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string()
+            }
+            Ok(relative) => relative.to_string_lossy().to_string(),
+            Err(_) => path.to_string_lossy().to_string(),
+        }
+    }
+
+    // 从文件夹内获取文件
+    fn walk_dir(&self, dir: &Path) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.error(&self.relative_path(dir), format!("读取目录失败: {}", e));
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            if self.cancelled() {
+                return;
+            }
+            let path = entry.path();
+            // 不跟随指向目录的符号链接，避免链接成环时无限遍历或重复扫描同一目录
+            let Ok(file_type) = entry.file_type() else {
+                self.walk_file(&path);
+                continue;
+            };
+            if file_type.is_dir() {
+                self.walk_dir(&path);
+            } else if file_type.is_symlink() && path.is_dir() {
+                self.skip(&self.relative_path(&path), "指向目录的符号链接，跳过");
+            } else {
+                self.walk_file(&path);
+            }
+        }
+    }
+
+    fn walk_file(&self, path: &Path) {
+        let file_name = self.relative_path(path);
+        let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+        if let Err(e) = self.walk_disk_file(path, &file_name) {
+            self.error(&file_name, e.to_string());
+        }
+        self.file_done(size);
+    }
+
+    fn walk_disk_file(&self, path: &Path, file_name: &str) -> ScanResult {
+        self.expanded.set(0);
+        let read_error = |e: std::io::Error| format!("读取失败: {}", e);
+        let mut file = BufReader::new(File::open(path).map_err(read_error)?);
+        let mut head = Vec::new();
+        file.by_ref().take(archive::SNIFF_LEN as u64).read_to_end(&mut head).map_err(read_error)?;
+        self.count(|summary| summary.files_visited += 1);
+        let format = archive::detect_format(&head, file_name);
+        // 磁盘上的 zip/7z 通过目录定位条目，tar 按顺序读取，均不整个读入内存
+        if let Some(format @ (Format::Zip | Format::SevenZ | Format::Tar)) = format {
+            file.seek(SeekFrom::Start(0)).map_err(read_error)?;
+            return self.walk_seekable(file, file_name, format);
+        }
+        let mut bytes = head;
+        file.read_to_end(&mut bytes).map_err(read_error)?;
+        if format.is_some() {
+            return self.walk_format(bytes, file_name, format);
+        }
+        // 目录中的文件不是 UTF-8 时按 GBK 读取
+        let contents = match archive::decode_text(&bytes) {
+            Some((contents, _)) => contents,
+            None => GBK.decode(&bytes).0.into_owned(),
+        };
+        self.submit(Job::Search {
+            contents,
+            file_name: file_name.to_string(),
+        });
+        Ok(())
+    }
+
+    fn walk_bytes(&self, bytes: Vec<u8>, file_name: &str) -> ScanResult {
+        self.count(|summary| summary.files_visited += 1);
+        let format = archive::detect_format(&bytes[..bytes.len().min(archive::SNIFF_LEN)], file_name);
+        self.walk_format(bytes, file_name, format)
+    }
+
+    // 按识别出的格式分发到压缩包、class 或文本处理，各层嵌套共用
+    fn walk_format(&self, bytes: Vec<u8>, file_name: &str, format: Option<Format>) -> ScanResult {
+        match format {
+            Some(format @ (Format::Zip | Format::SevenZ | Format::Tar)) => self.walk_seekable(Cursor::new(bytes), file_name, format),
+            Some(Format::Compressed(compression)) => {
+                self.enter_archive(file_name, || self.walk_compressed(&bytes, file_name, compression))
+            }
+            Some(Format::Class) => {
+                self.decompile_class(bytes, file_name);
+                Ok(())
+            }
+            // 解析出字符串表、XML 文本等后按文本匹配
+            Some(Format::Android(file)) => {
+                let contents = file.decode(&bytes).map_err(|e| format!("解析失败: {}", e))?;
+                self.submit(Job::Search {
+                    contents,
+                    file_name: file_name.to_string(),
+                });
+                Ok(())
+            }
+            None => {
+                self.walk_text(&bytes, file_name);
+                Ok(())
+            }
+        }
+    }
+
+    fn walk_text(&self, bytes: &[u8], file_name: &str) {
+        match archive::decode_text(bytes) {
+            Some((contents, _)) => self.submit(Job::Search {
+                contents,
+                file_name: file_name.to_string(),
+            }),
+            None => self.skip(file_name, "不是文本文件，跳过检索"),
+        }
+    }
+
+    fn walk_seekable<R: Read + Seek>(&self, mut reader: R, file_name: &str, format: Format) -> ScanResult {
+        self.enter_archive(file_name, || match format {
+            Format::SevenZ => self.walk_7z(reader, file_name),
+            Format::Tar => self.walk_tar(&mut reader, file_name),
+            _ => self.walk_zip(reader, file_name),
+        })
+    }
+
+    // 读取压缩包中的一个条目，compressed_size 用于检查压缩比
+    fn walk_entry(&self, reader: &mut dyn Read, relative_path: &str, compressed_size: u64) {
+        if self.expand_exhausted() {
+            return;
+        }
+        if let Err(e) = self.walk_stream(reader, relative_path, compressed_size) {
+            self.error(relative_path, e.to_string());
+        }
+    }
+
+    // 先读出文件头识别格式，嵌套的 zip/7z/tar 超过上限时转存到临时文件，同样不整个读入内存
+    fn walk_stream(&self, reader: &mut dyn Read, file_name: &str, compressed_size: u64) -> ScanResult {
+        let read_error = |e: std::io::Error| format!("解压失败: {}", e);
+        let mut reader = reader.take(self.expand_limit(compressed_size).saturating_add(1));
+        let mut head = Vec::new();
+        reader.by_ref().take(archive::SNIFF_LEN as u64).read_to_end(&mut head).map_err(read_error)?;
+        self.count(|summary| summary.files_visited += 1);
+        let format = archive::detect_format(&head, file_name);
+        let mut reader = Cursor::new(head).chain(reader);
+        if let Some(format @ (Format::Zip | Format::SevenZ | Format::Tar)) = format {
+            let mut nested = archive::spool(&mut reader, self.scanner.options.nested_buffer_limit).map_err(read_error)?;
+            let size = nested.seek(SeekFrom::End(0)).and_then(|size| nested.rewind().map(|_| size)).map_err(read_error)?;
+            if !self.within_limits(file_name, size, compressed_size, Some(format)) {
+                return Ok(());
+            }
+            return self.walk_seekable(nested, file_name, format);
+        }
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(read_error)?;
+        if !self.within_limits(file_name, bytes.len() as u64, compressed_size, format) {
+            return Ok(());
+        }
+        self.walk_format(bytes, file_name, format)
+    }
+
+    // 操作zip文件，war、jar也是
+    fn walk_zip<R: Read + Seek>(&self, reader: R, zip_path: &str) -> ScanResult {
+        let mut archive = ZipArchive::new(reader).map_err(|e| format!("无法打开压缩包: {}", e))?;
+        self.count(|summary| summary.archives_opened += 1);
+        for i in 0..archive.len() {
+            if self.cancelled() {
+                break;
+            }
+            // 单个条目失败不影响其他条目
+            let error = match archive.by_index(i) {
+                Ok(mut file) => {
+                    if file.is_file() {
+                        let relative_path = archive::entry_path(zip_path, &archive::decode_entry_name(file.name_raw()));
+                        let compressed_size = file.compressed_size();
+                        self.walk_entry(&mut file, &relative_path, compressed_size);
+                    }
+                    continue;
+                }
+                Err(e) => e,
+            };
+            match error {
+                ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => {
+                    self.walk_encrypted_zip_entry(&mut archive, i, zip_path)
+                }
+                e => self.error(&archive::entry_path(zip_path, &format!("#{}", i)), format!("读取压缩包条目失败: {}", e)),
+            }
+        }
+        Ok(())
+    }
+
+    // 加密的 zip 条目依次尝试配置的密码，都不正确时报告并跳过
+    fn walk_encrypted_zip_entry<R: Read + Seek>(&self, archive: &mut ZipArchive<R>, index: usize, zip_path: &str) {
+        let (relative_path, compressed_size) = match archive.by_index_raw(index) {
+            Ok(file) if !file.is_file() => return,
+            Ok(file) => (
+                archive::entry_path(zip_path, &archive::decode_entry_name(file.name_raw())),
+                file.compressed_size(),
+            ),
+            Err(e) => {
+                self.error(&archive::entry_path(zip_path, &format!("#{}", index)), format!("读取压缩包条目失败: {}", e));
+                return;
+            }
+        };
+        let limit = self.expand_limit(compressed_size);
+        for password in &self.scanner.options.zip_passwords {
+            let mut file = match archive.by_index_decrypt(index, password.as_bytes()) {
+                Ok(Ok(file)) => file,
+                Ok(Err(_)) => continue,  // 密码错误
+                Err(e) => {
+                    self.error(&relative_path, format!("读取压缩包条目失败: {}", e));
+                    return;
+                }
+            };
+            // ZipCrypto 只校验一个字节，错误的密码约有 1/256 的概率通过校验，解压时才出错
+            let mut data = Vec::new();
+            if file.by_ref().take(limit.saturating_add(1)).read_to_end(&mut data).is_ok() {
+                self.walk_entry(&mut Cursor::new(data), &relative_path, compressed_size);
+                return;
+            }
+        }
+        if self.scanner.options.zip_passwords.is_empty() {
+            self.skip(&relative_path, "zip 条目已加密，跳过");
+        } else {
+            self.skip(&relative_path, "zip 条目已加密，配置的密码都不正确，跳过");
+        }
+    }
+
+    // 操作7z文件，支持 LZMA/LZMA2 等压缩方式，加密的条目跳过
+    fn walk_7z<R: Read + Seek>(&self, mut reader: R, sevenz_path: &str) -> ScanResult {
+        let len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        let archive = match sevenz_rust::Archive::read(&mut reader, len, &[]) {
+            Ok(archive) => archive,
+            // 文件列表也加密时无法读取任何条目
+            Err(e) if is_7z_encrypted(&e) => {
+                self.skip(sevenz_path, "7z 压缩包已加密，跳过");
+                return Ok(());
+            }
+            Err(e) => return Err(format!("无法打开压缩包: {}", e).into()),
+        };
+        self.count(|summary| summary.archives_opened += 1);
+        for (folder_index, folder) in archive.folders.iter().enumerate() {
+            if self.cancelled() {
+                break;
+            }
+            let decoder = BlockDecoder::new(folder_index, &archive, &[], &mut reader);
+            let encrypted = folder.coders.iter().any(|coder| coder.decompression_method_id() == SevenZMethod::ID_AES256SHA256);
+            if encrypted {
+                for entry in decoder.entries().iter().filter(|entry| !entry.is_directory) {
+                    self.skip(&archive::entry_path(sevenz_path, &entry.name), "7z 条目已加密，跳过");
+                }
+                continue;
+            }
+            // 固实压缩的条目需要按顺序解压，单个条目失败时同一块中后面的条目也无法读取
+            let result = decoder.for_each_entries(&mut |entry, reader| {
+                if !entry.is_directory {
+                    // 固实压缩时只有块中第一个条目记录了整块压缩后的大小
+                    self.walk_entry(reader, &archive::entry_path(sevenz_path, &entry.name), entry.compressed_size);
+                }
+                // 条目没有读完时（如超出解压限制）需要读完剩余内容，同一块中后面的条目才能解压，读出的内容同样计入解压总量
+                let budget = self.scanner.options.limits.max_total_size.saturating_sub(self.expanded.get());
+                let drained = std::io::copy(&mut reader.take(budget.saturating_add(1)), &mut std::io::sink())?;
+                self.expanded.set(self.expanded.get().saturating_add(drained));
+                if drained > budget {
+                    let limits = &self.scanner.options.limits;
+                    self.limit_exceeded(sevenz_path, format!("{}，其余内容不再解压", limits.describe(LimitExceeded::TotalSize)));
+                    return Ok(false);
+                }
+                Ok(!self.cancelled())
+            });
+            if let Err(e) = result {
+                self.error(&archive::entry_path(sevenz_path, &format!("#{}", folder_index)), format!("解压失败: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    // 操作gz、xz、bz2、zst、lz4文件，解压后的内容同样按格式分发，多层压缩时继续解压
+    fn walk_compressed(&self, bytes: &[u8], path: &str, compression: Compression) -> ScanResult {
+        if self.expand_exhausted() {
+            return Ok(());
+        }
+        let compressed_size = bytes.len() as u64;
+        let decompressed_data = compression
+            .decompress(bytes, self.expand_limit(compressed_size))
+            .map_err(|e| format!("解压失败: {}", e))?;
+        self.count(|summary| summary.archives_opened += 1);
+        let format = archive::detect_decompressed_format(&decompressed_data, path);
+        if !self.within_limits(path, decompressed_data.len() as u64, compressed_size, format) {
+            return Ok(());
+        }
+        self.walk_format(decompressed_data, path, format)
+    }
+
+    // 操作tar文件：按顺序读取条目，条目未经压缩，与 zip 中存储的条目一样检查大小限制
+    fn walk_tar(&self, reader: &mut dyn Read, tar_path: &str) -> ScanResult {
+        self.count(|summary| summary.archives_opened += 1);
+        let mut tar = TarReader::new(reader);
+        while let Some(entry) = tar.next_entry()? {
+            if self.cancelled() {
+                break;
+            }
+            // 目录、链接等非普通文件没有可检索的内容
+            if !entry.is_file {
+                continue;
+            }
+            let relative_path = archive::entry_path(tar_path, &entry.name);
+            self.walk_entry(&mut tar.entry_data(), &relative_path, entry.size);
+        }
+        Ok(())
+    }
+
+    // 操作class文件：交给线程池通过 CFR 反编译后匹配源码
+    fn decompile_class(&self, bytes: Vec<u8>, class_path: &str) {
+        match &self.scanner.options.cfr_jar {
+            Some(cfr_jar) => self.submit(Job::Decompile {
+                bytes,
+                file_name: class_path.to_string(),
+                cfr_jar: cfr_jar.clone(),
+            }),
+            None => self.skip(class_path, "未配置反编译器，跳过 class 文件"),
+        }
+    }
+}
+
+// 运行 CFR，从标准输入写入 class 内容，返回反编译出的源码；取消时结束进程
+pub fn decompile(cfr_jar: &Path, bytes: &[u8], cancel: &CancelToken) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut command = Command::new("java");
+    command.arg("-jar")
+        .arg(cfr_jar)
+        .arg("--stdin")
+        .arg("class")
+        .stdout(Stdio::piped())  // 将标准输出重定向到管道
+        .stderr(Stdio::piped())  // 将标准错误重定向到管道
+        .stdin(Stdio::piped());
+    #[cfg(windows)]
+    {
+        command.creation_flags(0x08000000); // Windows 特定：创建隐藏窗口
+    }
+    let mut child = command.spawn()?;
+    let stdin = child.stdin.take();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    // 输入输出在单独的线程中读写，当前线程等待进程结束并检查是否取消
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(bytes);
+            }
+        });
+        let stdout = scope.spawn(move || read_pipe(stdout));
+        let stderr = scope.spawn(move || read_pipe(stderr));
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if cancel.is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("扫描已取消".into());
+            }
+            thread::sleep(CANCEL_POLL_INTERVAL);
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            return Err(format!("{}，{}", status, String::from_utf8_lossy(&stderr).trim()).into());
+        }
+        Ok(String::from_utf8_lossy(&stdout).to_string())
+    })
+}
+
+fn read_pipe(pipe: Option<impl Read>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut bytes);
+    }
+    bytes
+}
+
+// 统计路径下的文件数及总大小，用于估计进度
+fn count_files(path: &Path) -> (usize, u64) {
+    if path.is_file() {
+        return (1, path.metadata().map(|m| m.len()).unwrap_or(0));
+    }
+    let mut total = (0, 0);
+    if let Ok(entries) = std::fs::read_dir(path) {
+        for entry in entries.flatten() {
+            // 与遍历一致，不跟随指向目录的符号链接
+            if entry.file_type().is_ok_and(|file_type| file_type.is_symlink()) && entry.path().is_dir() {
+                continue;
+            }
+            let (files, bytes) = count_files(&entry.path());
+            total.0 += files;
+            total.1 += bytes;
+        }
+    }
+    total
+}
+
+// 未启用 aes256 特性时，加密的文件列表报告为不支持 AES 解压
+fn is_7z_encrypted(error: &sevenz_rust::Error) -> bool {
+    match error {
+        sevenz_rust::Error::PasswordRequired => true,
+        sevenz_rust::Error::UnsupportedCompressionMethod(method) => method == SevenZMethod::AES256SHA256.name(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::{zip_bytes, TempDir};
+    use crate::compliance::PHONE_RULE;
+    use crate::rule::Rule;
+
+    // zip -P secret 加密存储的 a.txt（内容为 "tel 13800138000"）及未加密的 plain.txt（内容为 "13900139000"）
+    const ENCRYPTED_ZIP: &[u8] = &[
+        0x50, 0x4b, 0x03, 0x04, 0x0a, 0x00, 0x09, 0x00, 0x00, 0x00, 0xac, 0x5d, 0x53, 0x5d, 0xec, 0xf9,
+        0x5e, 0xcc, 0x1b, 0x00, 0x00, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x61, 0x2e,
+        0x74, 0x78, 0x74, 0x37, 0xcf, 0xae, 0x35, 0x35, 0xc2, 0x70, 0x8c, 0x69, 0x03, 0x29, 0xe3, 0x8d,
+        0xf5, 0x6f, 0xc7, 0x9f, 0x58, 0xc8, 0xca, 0xbc, 0xed, 0xa9, 0x3f, 0x44, 0x9c, 0x1d, 0x50, 0x4b,
+        0x07, 0x08, 0xec, 0xf9, 0x5e, 0xcc, 0x1b, 0x00, 0x00, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x50, 0x4b,
+        0x03, 0x04, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0xac, 0x5d, 0x53, 0x5d, 0x17, 0xde, 0xeb, 0xd7,
+        0x0b, 0x00, 0x00, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x70, 0x6c, 0x61, 0x69,
+        0x6e, 0x2e, 0x74, 0x78, 0x74, 0x31, 0x33, 0x39, 0x30, 0x30, 0x31, 0x33, 0x39, 0x30, 0x30, 0x30,
+        0x50, 0x4b, 0x01, 0x02, 0x1e, 0x03, 0x0a, 0x00, 0x09, 0x00, 0x00, 0x00, 0xac, 0x5d, 0x53, 0x5d,
+        0xec, 0xf9, 0x5e, 0xcc, 0x1b, 0x00, 0x00, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa4, 0x81, 0x00, 0x00, 0x00, 0x00, 0x61, 0x2e,
+        0x74, 0x78, 0x74, 0x50, 0x4b, 0x01, 0x02, 0x1e, 0x03, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0xac,
+        0x5d, 0x53, 0x5d, 0x17, 0xde, 0xeb, 0xd7, 0x0b, 0x00, 0x00, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x09,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa4, 0x81, 0x4e, 0x00, 0x00,
+        0x00, 0x70, 0x6c, 0x61, 0x69, 0x6e, 0x2e, 0x74, 0x78, 0x74, 0x50, 0x4b, 0x05, 0x06, 0x00, 0x00,
+        0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x6a, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    fn scanner(options: ScanOptions) -> Scanner {
+        Scanner::new(RuleSet::new(vec![Rule::new(PHONE_RULE)]), ScanOptions { cfr_jar: None, ..options })
+    }
+
+    fn sevenz_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = sevenz_rust::SevenZWriter::new(Cursor::new(Vec::new())).unwrap();
+        for (name, data) in entries {
+            let mut entry = sevenz_rust::SevenZArchiveEntry::new();
+            entry.name = name.to_string();
+            entry.has_stream = true;
+            writer.push_archive_entry(entry, Some(*data)).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn finding_files(report: &ScanReport) -> Vec<&str> {
+        let mut files: Vec<_> = report.findings.iter().map(|finding| finding.file_name.as_str()).collect();
+        files.sort();
+        files
+    }
+
+    // 超出展开限制的结果：(文件名, 原因)
+    fn limit_findings(report: &ScanReport) -> Vec<(&str, &str)> {
+        report
+            .findings
+            .iter()
+            .filter(|finding| finding.rule == LIMIT_RULE)
+            .map(|finding| (finding.file_name.as_str(), finding.issue.as_deref().unwrap_or_default()))
+            .collect()
+    }
+
+    fn scan_limited(bytes: &[u8], limits: ArchiveLimits) -> ScanReport {
+        scanner(ScanOptions { limits, ..ScanOptions::default() }).scan_bytes(bytes, "app.zip")
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_dirs_are_skipped() {
+        let dir = TempDir::create();
+        dir.write("a/phone.txt", "tel 13800138000");
+        // 指向上级目录的链接成环，跟随时会无限遍历
+        std::os::unix::fs::symlink(&dir.path, dir.path.join("a/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.path.join("a/phone.txt"), dir.path.join("link.txt")).unwrap();
+        assert_eq!(count_files(&dir.path).0, 2);
+        let report = scanner(ScanOptions::default()).scan_path(&dir.path);
+        let mut files: Vec<_> = report.findings.iter().map(|finding| finding.file_name.as_str()).collect();
+        files.sort();
+        assert_eq!(files, vec![Path::new("a").join("phone.txt").to_str().unwrap(), "link.txt"]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].file_name, Path::new("a").join("loop").to_string_lossy());
+    }
+
+    #[test]
+    fn events_follow_scan_order() {
+        let dir = TempDir::create();
+        dir.write("one.txt", "13800138000\n13900139000");
+        dir.write("two.txt", "tel 13700137000");
+        dir.write("A.class", [0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let report = scanner(ScanOptions::default())
+            .on_event(move |event| sink.lock().unwrap().push(event))
+            .scan_path(&dir.path);
+        let events = events.lock().unwrap();
+
+        // 开始时报告统计出的总数，最后一次进度为全部完成
+        assert!(matches!(events.first(), Some(ScanEvent::Progress(p)) if p.files_total == 3 && p.files_done == 0));
+        let progress: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                ScanEvent::Progress(p) => Some(p.files_done),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress, vec![0, 1, 2, 3]);
+
+        // 每个文件先开始后结束，结束时的命中数与其间发出的命中一致
+        for name in ["one.txt", "two.txt"] {
+            let started = events.iter().position(|e| matches!(e, ScanEvent::FileStarted { file_name } if file_name == name)).unwrap();
+            let finished = events.iter().position(|e| matches!(e, ScanEvent::FileFinished { file_name, .. } if file_name == name)).unwrap();
+            assert!(started < finished);
+            let findings = events[started..finished].iter().filter(|e| matches!(e, ScanEvent::Finding(f) if f.file_name == name)).count();
+            assert!(matches!(&events[finished], ScanEvent::FileFinished { findings: n, .. } if *n == findings));
+        }
+        let findings = events.iter().filter(|e| matches!(e, ScanEvent::Finding(_))).count();
+        assert_eq!(findings, 3);
+        assert_eq!(findings, report.findings.len());
+        let skipped: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                ScanEvent::Skipped(issue) => Some(issue.file_name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(skipped, vec!["A.class"]);
+        assert!(!events.iter().any(|e| matches!(e, ScanEvent::Error(_))));
+    }
+
+    #[test]
+    fn cancel_stops_scan_midway() {
+        let dir = TempDir::create();
+        for i in 0..20 {
+            dir.write(&format!("{}.txt", i), "13800138000");
+        }
+        let cancel = CancelToken::new();
+        let token = cancel.clone();
+        // 第一个文件遍历完成后取消
+        let scanner = scanner(ScanOptions::default())
+            .with_cancel_token(cancel)
+            .on_event(move |event| {
+                if matches!(event, ScanEvent::Progress(p) if p.files_done == 1) {
+                    token.cancel();
+                }
+            });
+        let report = scanner.scan_path(&dir.path);
+        assert!(report.cancelled);
+        assert!(scanner.cancel_token().is_cancelled());
+        assert_eq!(report.summary.files_visited, 1);
+        assert!(report.findings.len() <= 1);
+    }
+
+    #[test]
+    fn summary_counts_files_archives_and_hits() {
+        let dir = TempDir::create();
+        let inner = zip_bytes(&[("c.txt", b"13700137000")]);
+        dir.write("app.zip", zip_bytes(&[("a.txt", b"13800138000 13900139000"), ("b.txt", b"none"), ("inner.zip", &inner)]));
+        dir.write("d.txt", "tel 13600136000");
+        let report = scanner(ScanOptions::default()).scan_path(&dir.path);
+        let summary = &report.summary;
+        // 目录中的 2 个文件，外层压缩包的 3 个条目及嵌套压缩包的 1 个条目
+        assert_eq!(summary.files_visited, 6);
+        assert_eq!(summary.archives_opened, 2);
+        assert_eq!(summary.bytes_scanned, 23 + 4 + 11 + 15);
+        assert_eq!(summary.limits_exceeded, 0);
+        assert_eq!(summary.rule_counts.get(PHONE_RULE), Some(&4));
+        let file_counts: Vec<_> = summary.file_counts.iter().map(|(file, count)| (file.as_str(), *count)).collect();
+        assert_eq!(file_counts, vec![("app.zip!/a.txt", 2), ("app.zip!/inner.zip!/c.txt", 1), ("d.txt", 1)]);
+        assert!(summary.rule_time.contains_key(PHONE_RULE));
+        assert!(report.skipped.is_empty() && report.errors.is_empty() && !report.cancelled);
+    }
+
+    #[test]
+    fn sevenz_entries_are_scanned() {
+        let nested = zip_bytes(&[("c.txt", b"13700137000")]);
+        let bytes = sevenz_bytes(&[("a.txt", b"13800138000"), ("dir/b.txt", b"none"), ("inner.zip", &nested)]);
+        let report = scanner(ScanOptions::default()).scan_bytes(&bytes, "app.7z");
+        assert_eq!(finding_files(&report), vec!["app.7z!/a.txt", "app.7z!/inner.zip!/c.txt"]);
+        assert_eq!(report.summary.archives_opened, 2);
+        assert_eq!(report.summary.files_visited, 5);
+        assert!(report.errors.is_empty());
+    }
+
+    #[test]
+    fn encrypted_zip_entries_try_configured_passwords() {
+        let scan = |passwords: &[&str]| {
+            let zip_passwords = passwords.iter().map(|password| password.to_string()).collect();
+            scanner(ScanOptions { zip_passwords, ..ScanOptions::default() }).scan_bytes(ENCRYPTED_ZIP, "secret.zip")
+        };
+        // 依次尝试配置的密码，错误的密码不影响后面的尝试
+        let report = scan(&["wrong", "secret"]);
+        assert_eq!(finding_files(&report), vec!["secret.zip!/a.txt", "secret.zip!/plain.txt"]);
+        assert!(report.skipped.is_empty() && report.errors.is_empty());
+
+        let report = scan(&["wrong"]);
+        assert_eq!(finding_files(&report), vec!["secret.zip!/plain.txt"]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].file_name, "secret.zip!/a.txt");
+        assert_eq!(report.skipped[0].reason, "zip 条目已加密，配置的密码都不正确，跳过");
+
+        let report = scan(&[]);
+        assert_eq!(finding_files(&report), vec!["secret.zip!/plain.txt"]);
+        assert_eq!(report.skipped[0].reason, "zip 条目已加密，跳过");
+    }
+
+    #[test]
+    fn nested_archives_beyond_max_depth_are_withheld() {
+        let inner = zip_bytes(&[("c.txt", b"13700137000")]);
+        let outer = zip_bytes(&[("a.txt", b"13800138000"), ("inner.zip", &inner)]);
+        let report = scan_limited(&outer, ArchiveLimits { max_depth: 1, ..ArchiveLimits::default() });
+        assert_eq!(finding_files(&report), vec!["app.zip!/a.txt", "app.zip!/inner.zip"]);
+        assert_eq!(limit_findings(&report), vec![("app.zip!/inner.zip", "压缩包嵌套超过 1 层，不再展开")]);
+        assert_eq!(report.summary.limits_exceeded, 1);
+    }
+
+    #[test]
+    fn entries_beyond_max_entry_size_are_withheld() {
+        let big = format!("13900139000{}", "x".repeat(100));
+        let bytes = zip_bytes(&[("big.txt", big.as_bytes()), ("small.txt", b"13800138000")]);
+        let report = scan_limited(&bytes, ArchiveLimits { max_entry_size: 64, ..ArchiveLimits::default() });
+        assert_eq!(finding_files(&report), vec!["app.zip!/big.txt", "app.zip!/small.txt"]);
+        assert_eq!(limit_findings(&report), vec![("app.zip!/big.txt", "解压后超过 64 字节，不再读取")]);
+    }
+
+    #[test]
+    fn entries_beyond_max_ratio_are_withheld() {
+        let bomb = vec![b'0'; 2 * 1024 * 1024];
+        let bytes = zip_bytes(&[("bomb.txt", &bomb), ("a.txt", b"13800138000")]);
+        let report = scan_limited(&bytes, ArchiveLimits::default());
+        assert_eq!(finding_files(&report), vec!["app.zip!/a.txt", "app.zip!/bomb.txt"]);
+        assert_eq!(limit_findings(&report), vec![("app.zip!/bomb.txt", "压缩比超过 200:1，疑似解压炸弹，不再读取")]);
+        assert_eq!(report.summary.bytes_scanned, 11);
+    }
+
+    #[test]
+    fn entries_beyond_max_total_size_are_withheld() {
+        let padded = format!("{:<40}", "13800138000");
+        let bytes = zip_bytes(&[("a.txt", padded.as_bytes()), ("b.txt", padded.as_bytes()), ("c.txt", b"13700137000")]);
+        let report = scan_limited(&bytes, ArchiveLimits { max_total_size: 64, ..ArchiveLimits::default() });
+        // 超出后同一文件中其余的条目不再解压
+        assert_eq!(finding_files(&report), vec!["app.zip!/a.txt", "app.zip!/b.txt"]);
+        assert_eq!(limit_findings(&report), vec![("app.zip!/b.txt", "所在文件解压总量超过 64 字节，其余内容不再解压")]);
+    }
+
+    #[test]
+    fn nested_archive_bytes_count_once() {
+        let contents = format!("{:<150}", "13700137000");
+        let inner = zip_bytes(&[("c.txt", contents.as_bytes())]);
+        let outer = zip_bytes(&[("inner.zip", &inner)]);
+        // 嵌套压缩包本身与其中的条目都计入时会超出
+        let max_total_size = inner.len().max(contents.len()) as u64 + 10;
+        let report = scan_limited(&outer, ArchiveLimits { max_total_size, ..ArchiveLimits::default() });
+        assert_eq!(finding_files(&report), vec!["app.zip!/inner.zip!/c.txt"]);
+        assert_eq!(report.summary.limits_exceeded, 0);
+    }
+}
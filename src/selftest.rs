@@ -0,0 +1,148 @@
+// 规则自测：用规则自带的示例检验规则，匹配方式与扫描时的 search() 相同
+use std::fmt;
+
+use crate::engine::Limits;
+use crate::rule::{CompiledRule, Rule, RuleError, YamlConfig};
+use crate::search;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestFailure {
+    // 规则无法编译
+    Invalid(RuleError),
+    // should_match 中的示例未命中
+    Missed {
+        name: String,
+        pattern: String,
+        example: String,
+    },
+    // should_not_match 中的示例被命中
+    Unexpected {
+        name: String,
+        pattern: String,
+        example: String,
+        matched: String,
+    },
+}
+
+impl fmt::Display for TestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TestFailure::Invalid(e) => write!(f, "{}", e),
+            TestFailure::Missed { name, example, .. } => write!(f, "{} 应当命中但未命中：{}", name, example),
+            TestFailure::Unexpected { name, example, matched, .. } => {
+                write!(f, "{} 不应命中但命中了 {}：{}", name, matched, example)
+            }
+        }
+    }
+}
+
+// limits 为扫描时的默认限制，规则按实际扫描时的引擎及限制编译
+pub fn test_rule(name: &str, rule: &Rule, limits: &Limits) -> Vec<TestFailure> {
+    if let Err(e) = rule.validate(name, limits) {
+        return vec![TestFailure::Invalid(e)];
+    }
+    let compiled = CompiledRule::new(name, rule, limits);
+    let Ok(matcher) = compiled.matcher() else {
+        return Vec::new();
+    };
+    let mut failures = Vec::new();
+    for example in &rule.should_match {
+        let matched = search(matcher, &rule.pattern, example, 0).iter().any(|(_, _, _, _, issue)| issue.is_none());
+        if !matched {
+            failures.push(TestFailure::Missed {
+                name: name.to_string(),
+                pattern: rule.pattern.clone(),
+                example: example.clone(),
+            });
+        }
+    }
+    for example in &rule.should_not_match {
+        let matches = search(matcher, &rule.pattern, example, 0);
+        if let Some((_, matched, _, _, _)) = matches.into_iter().find(|(_, _, _, _, issue)| issue.is_none()) {
+            failures.push(TestFailure::Unexpected {
+                name: name.to_string(),
+                pattern: rule.pattern.clone(),
+                example: example.clone(),
+                matched,
+            });
+        }
+    }
+    failures
+}
+
+// 测试配置文件中的所有规则，包括未启用的
+pub fn self_test(config: &YamlConfig, limits: &Limits) -> Vec<TestFailure> {
+    let mut failures = Vec::new();
+    for group in &config.rules {
+        for (i, rule) in group.patterns.iter().enumerate() {
+            failures.extend(test_rule(&format!("{} 第{}条", group.name, i + 1), rule, limits));
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, should_match: &[&str], should_not_match: &[&str]) -> Rule {
+        Rule {
+            should_match: should_match.iter().map(|s| s.to_string()).collect(),
+            should_not_match: should_not_match.iter().map(|s| s.to_string()).collect(),
+            ..Rule::new(pattern)
+        }
+    }
+
+    #[test]
+    fn passing_rule_has_no_failures() {
+        let rule = rule(r"(?<!\d)1\d{10}(?!\d)", &["tel=13800138000"], &["138001380001"]);
+        assert!(test_rule("手机号", &rule, &Limits::default()).is_empty());
+    }
+
+    #[test]
+    fn reports_missed_and_unexpected_examples() {
+        let rule = rule(r"key=\w+", &["key=abc", "key: abc"], &["key=", "monkey=1"]);
+        let failures = test_rule("密钥", &rule, &Limits::default());
+        assert_eq!(
+            failures,
+            vec![
+                TestFailure::Missed { name: "密钥".to_string(), pattern: r"key=\w+".to_string(), example: "key: abc".to_string() },
+                TestFailure::Unexpected {
+                    name: "密钥".to_string(),
+                    pattern: r"key=\w+".to_string(),
+                    example: "monkey=1".to_string(),
+                    matched: "key=1".to_string(),
+                },
+            ]
+        );
+        assert_eq!(failures[0].to_string(), "密钥 应当命中但未命中：key: abc");
+        assert_eq!(failures[1].to_string(), "密钥 不应命中但命中了 key=1：monkey=1");
+    }
+
+    #[test]
+    fn examples_use_builtin_checks() {
+        // 身份证号的校验位与扫描时一样参与判断
+        let rule = rule(crate::compliance::IDCARD_RULE, &["11010519491231002X"], &["110105194912310021"]);
+        assert!(test_rule("身份证号", &rule, &Limits::default()).is_empty());
+    }
+
+    #[test]
+    fn invalid_rule_is_reported_once() {
+        let rule = rule("(", &["a", "b"], &["c"]);
+        let failures = test_rule("坏规则", &rule, &Limits::default());
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(&failures[0], TestFailure::Invalid(e) if e.name == "坏规则"));
+    }
+
+    #[test]
+    fn self_test_covers_disabled_groups() {
+        let config: YamlConfig = serde_yaml::from_str(
+            "rules:\n\
+             - name: 日志\n  enabled: true\n  patterns:\n  - plain\n  - pattern: a+\n    should_match: [b]\n\
+             - name: 停用\n  enabled: false\n  patterns:\n  - pattern: x\n    should_not_match: [x]\n",
+        )
+        .unwrap();
+        let failures: Vec<String> = self_test(&config, &Limits::default()).iter().map(|f| f.to_string()).collect();
+        assert_eq!(failures, vec!["日志 第2条 应当命中但未命中：b", "停用 第1条 不应命中但命中了 x：x"]);
+    }
+}